@@ -0,0 +1,304 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use log::{info, trace};
+
+use crate::{
+    download_path, modz::LocalCollection, MEDIA_DIR, MODS_DIR, REGISTRY_FN, SCRATCH_PREFIX,
+};
+
+const ARCHIVE_EXTS: [&str; 3] = ["zip", "rar", "7z"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntryKind {
+    Archive,
+    Extracted,
+    Media,
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    pub kind: EntryKind,
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: SystemTime,
+    /// Mod id taken from the cache layout, None for old flat downloads
+    pub mod_id: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+pub struct CacheStats {
+    pub archives: u64,
+    pub extracted: u64,
+    pub media: u64,
+    pub entries: usize,
+    pub orphans: Vec<CacheEntry>,
+}
+
+impl CacheStats {
+    pub fn total(&self) -> u64 {
+        self.archives + self.extracted + self.media
+    }
+
+    pub fn orphaned(&self) -> u64 {
+        self.orphans.iter().map(|e| e.size).sum()
+    }
+}
+
+/// Looks over everything inside `download_path()` and compares it against what the
+/// registry still points to
+pub struct Cache {
+    root: PathBuf,
+    referenced: Vec<PathBuf>,
+    ids: Vec<usize>,
+    /// Previews local mods show offline, kept even by prune
+    previews: Vec<PathBuf>,
+}
+
+impl Cache {
+    pub fn new(col: &LocalCollection) -> Result<Cache> {
+        Ok(Self::with_root(download_path()?, col))
+    }
+
+    pub fn with_root(root: PathBuf, col: &LocalCollection) -> Cache {
+        Cache {
+            root,
+            referenced: col.mods.iter().map(|m| m.path().to_path_buf()).collect(),
+            ids: col.mods.iter().map(|m| m.id).collect(),
+            previews: col.mods.iter().flat_map(|m| m.previews.clone()).collect(),
+        }
+    }
+
+    pub fn entries(&self) -> Result<Vec<CacheEntry>> {
+        let mut entries = Vec::new();
+        for top in fs::read_dir(&self.root)? {
+            let path = top?.path();
+            match path.file_name().and_then(|n| n.to_str()) {
+                Some(REGISTRY_FN) => (),
+                Some(MODS_DIR) => {
+                    for (id, mod_dir) in numbered_dirs(&path)? {
+                        for (_, file_dir) in numbered_dirs(&mod_dir)? {
+                            for entry in fs::read_dir(file_dir)? {
+                                let path = entry?.path();
+                                // Downloads in progress, they clean up after themselves
+                                if !is_scratch(&path) {
+                                    entries.push(describe(&path, Some(id))?);
+                                }
+                            }
+                        }
+                    }
+                }
+                Some(MEDIA_DIR) => {
+                    for (id, media_dir) in numbered_dirs(&path)? {
+                        for entry in fs::read_dir(media_dir)? {
+                            entries.push(describe(&entry?.path(), Some(id))?);
+                        }
+                    }
+                }
+                _ => entries.push(describe(&path, None)?),
+            }
+        }
+        trace!(count = entries.len(), path:? = self.root; "Found cache entries");
+        Ok(entries)
+    }
+
+    pub fn is_orphan(&self, entry: &CacheEntry) -> bool {
+        match entry.kind {
+            EntryKind::Extracted => !self.references(&entry.path),
+            EntryKind::Archive => !self.references(&entry.path.with_extension("")),
+            EntryKind::Media => !entry.mod_id.is_some_and(|id| self.ids.contains(&id)),
+        }
+    }
+
+    fn references(&self, path: &Path) -> bool {
+        self.referenced.iter().any(|r| r.starts_with(path))
+    }
+
+    pub fn stats(&self) -> Result<CacheStats> {
+        let mut stats = CacheStats::default();
+        for entry in self.entries()? {
+            match entry.kind {
+                EntryKind::Archive => stats.archives += entry.size,
+                EntryKind::Extracted => stats.extracted += entry.size,
+                EntryKind::Media => stats.media += entry.size,
+            }
+            stats.entries += 1;
+            if self.is_orphan(&entry) {
+                stats.orphans.push(entry);
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Removes everything the registry no longer refers to
+    pub fn clean(&self) -> Result<Vec<CacheEntry>> {
+        self.remove_where(|entry| self.is_orphan(entry))
+    }
+
+    /// Like clean but restricted to entries older than `age`, also drops archives and
+    /// gallery images of registered mods since those can be downloaded again. The
+    /// previews their manage pane shows offline stay
+    pub fn prune(&self, age: Duration) -> Result<Vec<CacheEntry>> {
+        let cutoff = SystemTime::now()
+            .checked_sub(age)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        self.remove_where(|entry| {
+            let kept = match entry.kind {
+                EntryKind::Extracted => !self.is_orphan(entry),
+                EntryKind::Media => self.previews.contains(&entry.path),
+                EntryKind::Archive => false,
+            };
+            entry.modified < cutoff && !kept
+        })
+    }
+
+    fn remove_where(&self, pred: impl Fn(&CacheEntry) -> bool) -> Result<Vec<CacheEntry>> {
+        let removed: Vec<CacheEntry> = self.entries()?.into_iter().filter(pred).collect();
+        for entry in &removed {
+            info!(path:? = entry.path, mod_id:? = entry.mod_id; "Removing cache entry");
+            if entry.path.is_dir() {
+                fs::remove_dir_all(&entry.path)?;
+            } else {
+                fs::remove_file(&entry.path)?;
+            }
+        }
+        remove_empty_dirs(&self.root)?;
+        Ok(removed)
+    }
+}
+
+fn is_scratch(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.starts_with(SCRATCH_PREFIX))
+}
+
+fn numbered_dirs(path: &Path) -> Result<Vec<(usize, PathBuf)>> {
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let path = entry?.path();
+        let id = path.file_name().and_then(|n| n.to_str()?.parse().ok());
+        if let (Some(id), true) = (id, path.is_dir()) {
+            dirs.push((id, path));
+        }
+    }
+    Ok(dirs)
+}
+
+fn describe(path: &Path, mod_id: Option<usize>) -> Result<CacheEntry> {
+    let kind = if path.is_dir() {
+        EntryKind::Extracted
+    } else if path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ARCHIVE_EXTS.contains(&ext.to_lowercase().as_str()))
+    {
+        EntryKind::Archive
+    } else {
+        EntryKind::Media
+    };
+    Ok(CacheEntry {
+        kind,
+        path: path.to_path_buf(),
+        size: disk_usage(path)?,
+        modified: fs::metadata(path)?.modified()?,
+        mod_id,
+    })
+}
+
+fn disk_usage(path: &Path) -> Result<u64> {
+    let meta = fs::symlink_metadata(path)?;
+    if !meta.is_dir() {
+        return Ok(meta.len());
+    }
+    let mut total = 0;
+    for entry in fs::read_dir(path)? {
+        total += disk_usage(&entry?.path())?;
+    }
+    Ok(total)
+}
+
+fn remove_empty_dirs(root: &Path) -> Result<()> {
+    for sub in [MODS_DIR, MEDIA_DIR] {
+        let path = root.join(sub);
+        if !path.is_dir() {
+            continue;
+        }
+        for (_, outer) in numbered_dirs(&path)? {
+            for (_, inner) in numbered_dirs(&outer)? {
+                if fs::read_dir(&inner)?.next().is_none() {
+                    fs::remove_dir(&inner)?;
+                }
+            }
+            if fs::read_dir(&outer)?.next().is_none() {
+                fs::remove_dir(&outer)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn orphans_found() {
+        let root = env::temp_dir().join("ggmod-cache-test");
+        let kept = root.join(MODS_DIR).join("1").join("10").join("kept");
+        let gone = root.join(MODS_DIR).join("2").join("20").join("gone");
+        let scratch = root.join(MODS_DIR).join("1").join("10").join(".part-1-0");
+        for dir in [&kept, &gone, &scratch, &root.join(MEDIA_DIR).join("2")] {
+            fs::DirBuilder::new().recursive(true).create(dir).unwrap();
+        }
+        fs::write(kept.with_extension("zip"), b"zip").unwrap();
+        fs::write(root.join(MEDIA_DIR).join("2").join("a.jpg"), b"jpg").unwrap();
+        let cache = Cache {
+            root: root.clone(),
+            referenced: vec![kept.clone()],
+            ids: vec![1],
+            previews: Vec::new(),
+        };
+        let orphans: Vec<PathBuf> = cache
+            .stats()
+            .unwrap()
+            .orphans
+            .into_iter()
+            .map(|e| e.path)
+            .collect();
+        assert_eq!(orphans.len(), 2);
+        assert!(orphans.contains(&gone));
+        cache.clean().unwrap();
+        assert!(kept.is_dir() && kept.with_extension("zip").is_file());
+        assert!(scratch.is_dir());
+        assert!(!root.join(MODS_DIR).join("2").exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn prune_keeps_shown_previews() {
+        let root = env::temp_dir().join("ggmod-prune-test");
+        let media = root.join(MEDIA_DIR).join("1");
+        fs::DirBuilder::new()
+            .recursive(true)
+            .create(&media)
+            .unwrap();
+        let (shown, other) = (media.join("a.jpg"), media.join("b.jpg"));
+        fs::write(&shown, b"jpg").unwrap();
+        fs::write(&other, b"jpg").unwrap();
+        let cache = Cache {
+            root: root.clone(),
+            referenced: Vec::new(),
+            ids: vec![1],
+            previews: vec![shown.clone()],
+        };
+        cache.prune(Duration::ZERO).unwrap();
+        assert!(shown.is_file() && !other.exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,49 +1,40 @@
-use std::io::{self, BufRead};
+mod batch;
+mod output;
+mod search;
+pub use batch::*;
+pub use output::*;
+pub use search::*;
 
-use crate::gamebanana::models::modpage::GBModPage;
+use std::{
+    io::{self, BufRead},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+
+use serde::Serialize;
 
-use super::{
-    gamebanana::builder::{FeedFilter, SearchBuilder, SearchFilter},
-    modz::LocalCollection,
+use crate::{
+    cache::Cache,
+    error::{classify, GGModError},
+    gamebanana::{
+        format_date, html_to_text,
+        http::{self, Progress},
+        human_size,
+        models::{file::GBFile, modpage::GBModPage},
+    },
+    modz::Mod,
 };
 
-pub async fn search(
-    page: usize,
-    page_size: Option<usize>,
-    name: Option<String>,
-    _featured: bool,
-    popular: bool,
-    recent: bool,
-) {
-    let entries = SearchBuilder::new()
-        .per_page(page_size.unwrap_or(15))
-        .with_sort(if recent {
-            FeedFilter::Recent
-        } else if popular {
-            FeedFilter::Popular
-        } else {
-            FeedFilter::Featured
-        })
-        .by_search(SearchFilter::Name {
-            search: &name.unwrap_or(String::from("")),
-            game_id: 11534,
-        })
-        .build()
-        .read_page(page)
-        .await
-        .expect("Couldn't get search results");
-    for entry in entries {
-        let mut name = entry.name.clone();
-        name.truncate(35);
-        let mut desc = entry.description.clone();
-        desc.truncate(50);
-        let views = entry.view_count;
-        println!("{name:<35} - {desc:<50} :: {views} views");
-    }
-}
-
-pub fn list_all(col: LocalCollection) {
-    for mod_ in &col.mods {
+use super::modz::LocalCollection;
+
+pub fn list_all(col: &LocalCollection, format: OutputFormat) -> Result<()> {
+    print_mods(&col.mods, format);
+    Ok(())
+}
+
+fn print_mods(mods: &[Mod], format: OutputFormat) {
+    print_records(mods, format, |mod_| {
         println!(
             "[{}] [{}] {}: {}",
             if mod_.staged { "+" } else { " " },
@@ -51,42 +42,441 @@ pub fn list_all(col: LocalCollection) {
             mod_.id,
             mod_.name
         )
+    });
+}
+
+#[derive(Serialize)]
+struct InfoRecord<'a> {
+    #[serde(flatten)]
+    page: &'a GBModPage,
+    local: Vec<&'a Mod>,
+}
+
+pub async fn info(col: &LocalCollection, mod_id: usize, format: OutputFormat) -> Result<()> {
+    let local: Vec<&Mod> = col.mods.iter().filter(|m| m.id == mod_id).collect();
+    let gbmod = match GBModPage::build(mod_id).await {
+        Ok(page) => page,
+        // What we have on disk is still worth showing without GameBanana
+        Err(e) if !local.is_empty() && classify(&e).is_unreachable() => {
+            eprintln!("warning: {e:#}, showing the local copy only");
+            let local: Vec<Mod> = local.into_iter().cloned().collect();
+            print_mods(&local, format);
+            return Ok(());
+        }
+        Err(e) => return Err(e.context(format!("Couldn't get mod page for {mod_id}"))),
+    };
+    match format {
+        OutputFormat::Json => {
+            let record = InfoRecord {
+                page: &gbmod,
+                local,
+            };
+            println!("{}", serde_json::to_string_pretty(&record)?)
+        }
+        OutputFormat::Csv => print_records(&gbmod.files, format, |_| ()),
+        OutputFormat::Table => print_info(&gbmod, &local),
+    }
+    Ok(())
+}
+
+fn print_info(gbmod: &GBModPage, local: &[&Mod]) {
+    println!("{} [{}]", gbmod.name, gbmod.row);
+    if !gbmod.profile_url.is_empty() {
+        println!("{}", gbmod.profile_url);
+    }
+    if let Some(submitter) = &gbmod.submitter {
+        println!("Submitter: {} [{}]", submitter.name, submitter.row);
+    }
+    match &gbmod.super_category {
+        Some(sup) => println!("Category:  {} ({})", gbmod.category.name, sup.name),
+        None => println!("Category:  {}", gbmod.category.name),
+    }
+    if gbmod.date_added != 0 {
+        println!(
+            "Added:     {}, updated {}",
+            format_date(gbmod.date_added),
+            format_date(gbmod.date_updated.max(gbmod.date_added))
+        );
+    }
+    println!("NSFW:      {}", if gbmod.is_nsfw { "yes" } else { "no" });
+    if !gbmod.tags.is_empty() {
+        let tags: Vec<String> = gbmod.tags.iter().map(ToString::to_string).collect();
+        println!("Tags:      {}", tags.join(", "));
+    }
+    if local.is_empty() {
+        println!("Local:     not downloaded");
+    }
+    for m in local {
+        let state = if m.staged {
+            "installed"
+        } else {
+            "not installed"
+        };
+        println!("Local:     {} ({state})", m.variant);
+    }
+    for text in [&gbmod.description, &gbmod.text] {
+        let text = html_to_text(text);
+        if !text.is_empty() {
+            println!("\n{text}");
+        }
+    }
+    println!("\nFiles:");
+    for (i, f) in gbmod.files.iter().enumerate() {
+        println!(
+            "[{}] {} - {}, added {}, {} downloads",
+            i + 1,
+            f.file,
+            human_size(f.filesize as u64),
+            format_date(f.date_added),
+            f.download_count
+        );
+        let desc = html_to_text(&f.description);
+        if !desc.is_empty() {
+            println!("    {desc}");
+        }
+    }
+    if !gbmod.alternate_file_sources.is_empty() {
+        println!("\nAlternate sources:");
+    }
+    for source in &gbmod.alternate_file_sources {
+        if source.description.is_empty() {
+            println!("{}", source.url);
+        } else {
+            println!("{} - {}", source.url, source.description);
+        }
+    }
+    if !gbmod.latest_updates.is_empty() {
+        println!("\nUpdates:");
+    }
+    for update in &gbmod.latest_updates {
+        let version = if update.version.is_empty() {
+            String::new()
+        } else {
+            format!(" v{}", update.version)
+        };
+        println!(
+            "{}{version} - {}",
+            format_date(update.date_added),
+            update.name
+        );
+        for change in &update.change_log {
+            let cat = if change.cat.is_empty() {
+                String::new()
+            } else {
+                format!("[{}] ", change.cat)
+            };
+            println!("    {cat}{}", html_to_text(&change.text));
+        }
+    }
+}
+
+/// How `download` settles on one of the files of a mod page
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileSelector {
+    Prompt,
+    /// 1-based index as listed, exact file name or GameBanana file id
+    Pick(String),
+    Latest,
+    Largest,
+}
+
+impl FileSelector {
+    pub fn choose(&self, files: &[GBFile]) -> Result<usize> {
+        if files.is_empty() {
+            bail!("This mod has no downloadable files");
+        }
+        match self {
+            FileSelector::Prompt => {
+                for (i, f) in files.iter().enumerate() {
+                    println!("[{}] {:?}", (i + 1), f.file);
+                }
+                println!("Choose index:");
+                Ok(choose_num(files.len())? - 1)
+            }
+            FileSelector::Pick(pick) => pick_file(files, pick),
+            FileSelector::Latest => Ok(max_index_by_key(files, |f| f.date_added)),
+            FileSelector::Largest => Ok(max_index_by_key(files, |f| f.filesize)),
+        }
+    }
+}
+
+fn pick_file(files: &[GBFile], pick: &str) -> Result<usize> {
+    if let Ok(n) = pick.trim().parse::<usize>() {
+        if (1..=files.len()).contains(&n) {
+            return Ok(n - 1);
+        }
+        if let Some(idx) = files.iter().position(|f| f.row == n) {
+            return Ok(idx);
+        }
+        return Err(usage(format!(
+            "No file with index or id {n}, pick an index between 1 and {} or one of the ids: {}",
+            files.len(),
+            files
+                .iter()
+                .map(|f| f.row.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+    files
+        .iter()
+        .position(|f| f.file.eq_ignore_ascii_case(pick.trim()))
+        .ok_or_else(|| {
+            usage(format!(
+                "No file named {pick:?}, available files are: {}",
+                files
+                    .iter()
+                    .map(|f| f.file.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })
+}
+
+fn max_index_by_key(files: &[GBFile], key: impl Fn(&GBFile) -> usize) -> usize {
+    files
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, f)| key(f))
+        .map_or(0, |(i, _)| i)
+}
+
+/// Mod pages are fetched and files downloaded `jobs` at a time, picking files happens
+/// in between so prompts don't interleave
+pub async fn download(
+    col: &mut LocalCollection,
+    mod_ids: Vec<usize>,
+    selector: FileSelector,
+    do_install: bool,
+    jobs: usize,
+) -> Result<()> {
+    let mut outcomes = Vec::new();
+    let pages = bounded(mod_ids.clone(), jobs, |id| async move {
+        GBModPage::build(id)
+            .await
+            .context("Couldn't get online mod page")
+    })
+    .await;
+    let mut chosen = Vec::new();
+    for (id, page) in mod_ids.into_iter().zip(pages) {
+        let gbmod = match page.and_then(|page| page) {
+            Ok(gbmod) => gbmod,
+            Err(e) => {
+                outcomes.push(Outcome::new(id, "?", Status::Failed(e)));
+                continue;
+            }
+        };
+        if selector == FileSelector::Prompt {
+            println!("Files for {}:", gbmod.name);
+        }
+        match selector.choose(&gbmod.files) {
+            Ok(idx) => chosen.push((gbmod, idx)),
+            Err(e) => outcomes.push(Outcome::new(id, &gbmod.name, Status::Failed(e))),
+        }
+    }
+    let names: Vec<_> = chosen
+        .iter()
+        .map(|(m, _)| (m.row, m.name.clone()))
+        .collect();
+    let downloads = bounded(chosen, jobs, |(gbmod, idx)| async move {
+        Mod::build(gbmod, idx, &Progress::default()).await
+    })
+    .await;
+    for ((id, name), res) in names.into_iter().zip(downloads) {
+        let status = match res.and_then(|res| res) {
+            Ok(mut new_mod) => {
+                let variant = new_mod.variant.clone();
+                let staged = if do_install {
+                    new_mod.stage().map(|_| " and installed")
+                } else {
+                    Ok("")
+                };
+                col.register(new_mod);
+                match staged {
+                    Ok(suffix) => Status::Done(format!("downloaded {variant}{suffix}")),
+                    Err(e) => Status::Failed(e.context(format!("downloaded {variant}"))),
+                }
+            }
+            Err(e) => Status::Failed(e),
+        };
+        outcomes.push(Outcome::new(id, &name, status));
+    }
+    summarize(outcomes)
+}
+
+pub fn install(col: &mut LocalCollection, query: &ModQuery) -> Result<()> {
+    let outcomes = apply_matching(col, query, |m| {
+        if m.staged {
+            Status::Skipped(String::from("already installed"))
+        } else {
+            m.stage()
+                .map_or_else(Status::Failed, |_| Status::Done(String::from("installed")))
+        }
+    });
+    summarize(outcomes)
+}
+
+pub fn uninstall(col: &mut LocalCollection, query: &ModQuery) -> Result<()> {
+    let outcomes = apply_matching(col, query, |m| {
+        if !m.staged {
+            Status::Skipped(String::from("not installed"))
+        } else {
+            m.unstage().map_or_else(Status::Failed, |_| {
+                Status::Done(String::from("uninstalled"))
+            })
+        }
+    });
+    summarize(outcomes)
+}
+
+fn apply_matching(
+    col: &mut LocalCollection,
+    query: &ModQuery,
+    fun: impl Fn(&mut Mod) -> Status,
+) -> Vec<Outcome> {
+    let mut outcomes: Vec<Outcome> = col
+        .mods
+        .iter_mut()
+        .filter(|m| query.matches(m))
+        .map(|m| Outcome::new(m.id, &m.name.clone(), fun(m)))
+        .collect();
+    for id in &query.mod_ids {
+        if !col.mods.iter().any(|m| m.id == *id) {
+            let e = usage(String::from(
+                "not in the local collection, download it first",
+            ));
+            outcomes.push(Outcome::new(*id, "?", Status::Failed(e)));
+        }
     }
+    outcomes
 }
 
-pub async fn download(mut col: LocalCollection, mod_id: usize, do_install: bool) {
-    let gbmod = GBModPage::build(mod_id)
-        .await
-        .expect("Couldn't get online mod page");
-    let opts = &gbmod.files;
-    for (i, f) in opts.iter().enumerate() {
-        println!("[{}] {:?}", (i + 1), f.file);
+fn summarize(mut outcomes: Vec<Outcome>) -> Result<()> {
+    print_summary(&outcomes);
+    let failed = outcomes.iter().filter(|o| o.failed()).count();
+    if failed == 0 {
+        return Ok(());
+    }
+    // A lone failure is passed on as is so it keeps its own exit code
+    if outcomes.len() == 1 {
+        if let Status::Failed(e) = outcomes.remove(0).status {
+            return Err(e);
+        }
     }
-    println!("Choose index:");
-    let input = choose_num() - 1;
-    col.register_online_mod(gbmod, input)
-        .await
-        .expect("Couldn't download mod");
-    if do_install {
-        install(col, mod_id)
+    Err(GGModError::Batch {
+        failed,
+        total: outcomes.len(),
+    }
+    .into())
+}
+
+pub fn cache_stats(col: &LocalCollection) -> Result<()> {
+    let stats = Cache::new(col)
+        .and_then(|cache| cache.stats())
+        .context("Couldn't read download cache")?;
+    println!("Archives:  {}", human_size(stats.archives));
+    println!("Extracted: {}", human_size(stats.extracted));
+    println!("Previews:  {}", human_size(stats.media));
+    println!(
+        "Total:     {} in {} entries",
+        human_size(stats.total()),
+        stats.entries
+    );
+    println!(
+        "Orphaned:  {} in {} entries",
+        human_size(stats.orphaned()),
+        stats.orphans.len()
+    );
+    for orphan in &stats.orphans {
+        println!(
+            "  {:>10} {}",
+            human_size(orphan.size),
+            orphan.path.display()
+        );
     }
+    Ok(())
 }
 
-pub fn install(mut col: LocalCollection, mod_id: usize) {
-    col.apply_on_mod(mod_id, Box::new(|mod_| mod_.stage()))
-        .expect("add ");
+pub fn cache_clean(col: &LocalCollection) -> Result<()> {
+    let removed = Cache::new(col)
+        .and_then(|cache| cache.clean())
+        .context("Couldn't clean download cache")?;
+    let freed = removed.iter().map(|e| e.size).sum();
+    println!(
+        "Removed {} entries, freed {}",
+        removed.len(),
+        human_size(freed)
+    );
+    Ok(())
 }
 
-pub fn uninstall(mut col: LocalCollection, mod_id: usize) {
-    col.apply_on_mod(mod_id, Box::new(|mod_| mod_.unstage()))
-        .expect("couldnt rempve stuf");
+pub fn cache_prune(col: &LocalCollection, days: u64) -> Result<()> {
+    let age = Duration::from_secs(days * 24 * 60 * 60);
+    let removed = Cache::new(col)
+        .and_then(|cache| cache.prune(age))
+        .context("Couldn't prune download cache")?;
+    let responses = http::purge(age).context("Couldn't prune cached responses")?;
+    let freed = removed.iter().map(|e| e.size).sum::<u64>() + responses;
+    println!(
+        "Removed {} entries, freed {}",
+        removed.len(),
+        human_size(freed)
+    );
+    Ok(())
 }
 
 /// We use this since the user won't necessarily know what files a mod will include
-/// beforehand
-fn choose_num() -> usize {
+/// beforehand, keeps asking until we get a number in 1..=max
+fn choose_num(max: usize) -> Result<usize> {
     let stdin = io::stdin();
-    let mut iterator = stdin.lock().lines();
-    let input = iterator.next().unwrap().unwrap();
-    input.trim().parse::<usize>().unwrap()
+    for line in stdin.lock().lines() {
+        match line?.trim().parse::<usize>() {
+            Ok(n) if (1..=max).contains(&n) => return Ok(n),
+            _ => println!("Please enter a number between 1 and {max}:"),
+        }
+    }
+    Err(usage(String::from(
+        "No file chosen, pass --file, --latest or --largest when not running interactively",
+    )))
+}
+
+fn usage(s: String) -> anyhow::Error {
+    GGModError::Usage(s).into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn file(row: usize, name: &str, date_added: usize, filesize: usize) -> GBFile {
+        GBFile {
+            row,
+            contains_exe: false,
+            download_count: 0,
+            filesize,
+            analysis_result_code: String::from("ok"),
+            date_added,
+            file: String::from(name),
+            download_url: String::new(),
+            description: String::new(),
+        }
+    }
+
+    #[test]
+    fn selectors_pick_files() {
+        let files = [
+            file(1001, "sol_red.zip", 30, 5),
+            file(1002, "sol_blue.zip", 10, 50),
+            file(1003, "sol_green.7z", 20, 1),
+        ];
+        let pick = |s: &str| FileSelector::Pick(String::from(s)).choose(&files).ok();
+        assert_eq!(pick("2"), Some(1));
+        assert_eq!(pick("1003"), Some(2));
+        assert_eq!(pick("SOL_BLUE.zip"), Some(1));
+        assert_eq!(pick("4"), None);
+        assert_eq!(pick("sol_black.zip"), None);
+        assert_eq!(FileSelector::Latest.choose(&files).ok(), Some(0));
+        assert_eq!(FileSelector::Largest.choose(&files).ok(), Some(1));
+        assert!(FileSelector::Latest.choose(&[]).is_err());
+    }
 }
@@ -0,0 +1,143 @@
+use std::{collections::HashMap, future::Future, sync::Arc};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use crate::modz::Mod;
+
+/// Which local mods a command should touch, ids and filters are combined with OR
+#[derive(Args, Debug, Clone, Default)]
+#[group(required = true, multiple = true)]
+pub struct ModQuery {
+    /// Mod IDs
+    pub mod_ids: Vec<usize>,
+
+    /// Every mod in the collection
+    #[arg(short, long)]
+    pub all: bool,
+
+    /// Mods for this character
+    #[arg(short, long)]
+    pub character: Option<String>,
+
+    /// Only NSFW mods
+    #[arg(long)]
+    pub nsfw: bool,
+}
+
+impl ModQuery {
+    fn filtered(&self) -> bool {
+        self.all || self.character.is_some() || self.nsfw
+    }
+
+    pub fn matches(&self, m: &Mod) -> bool {
+        self.mod_ids.contains(&m.id)
+            || (self.filtered()
+                && self
+                    .character
+                    .as_ref()
+                    .is_none_or(|c| m.character.eq_ignore_ascii_case(c))
+                && (!self.nsfw || m.is_nsfw))
+    }
+}
+
+pub enum Status {
+    Done(String),
+    Skipped(String),
+    Failed(anyhow::Error),
+}
+
+pub struct Outcome {
+    pub id: usize,
+    pub name: String,
+    pub status: Status,
+}
+
+impl Outcome {
+    pub fn new(id: usize, name: &str, status: Status) -> Outcome {
+        Outcome {
+            id,
+            name: name.to_string(),
+            status,
+        }
+    }
+
+    pub fn failed(&self) -> bool {
+        matches!(self.status, Status::Failed(_))
+    }
+}
+
+/// Prints one line per mod plus totals
+pub fn print_summary(outcomes: &[Outcome]) {
+    if outcomes.is_empty() {
+        println!("Nothing matched");
+        return;
+    }
+    let name_width = outcomes
+        .iter()
+        .map(|o| o.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .clamp(4, 40);
+    println!("{:<8} {:<name_width$} {:<7} Detail", "ID", "Name", "Status");
+    for o in outcomes {
+        let (status, detail) = match &o.status {
+            Status::Done(s) => ("ok", s.clone()),
+            Status::Skipped(s) => ("skipped", s.clone()),
+            Status::Failed(e) => ("failed", format!("{e:#}")),
+        };
+        let name: String = o.name.chars().take(name_width).collect();
+        println!("{:<8} {name:<name_width$} {status:<7} {detail}", o.id);
+    }
+    let failed = outcomes.iter().filter(|o| o.failed()).count();
+    println!("{} succeeded, {failed} failed", outcomes.len() - failed);
+}
+
+/// Runs `f` over every item with at most `jobs` running at once, keeps input order. A
+/// task that panics comes back as an error instead of taking the rest down with it
+pub async fn bounded<T, R, F, Fut>(items: Vec<T>, jobs: usize, f: F) -> Vec<Result<R>>
+where
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = R> + Send + 'static,
+    R: Send + 'static,
+{
+    let permits = Arc::new(Semaphore::new(jobs.max(1)));
+    let mut set = JoinSet::new();
+    let mut order = HashMap::new();
+    for (i, item) in items.into_iter().enumerate() {
+        let permits = permits.clone();
+        let task = f(item);
+        let handle = set.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            task.await
+        });
+        order.insert(handle.id(), i);
+    }
+    let mut results = Vec::new();
+    while let Some(res) = set.join_next_with_id().await {
+        results.push(match res {
+            Ok((id, r)) => (order[&id], Ok(r)),
+            Err(e) => (order[&e.id()], Err(e).context("Batch task failed")),
+        });
+    }
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn panics_become_errors() {
+        let results = bounded(vec![1, 2, 3], 2, |n| async move {
+            assert_ne!(n, 2, "no twos");
+            n * 10
+        })
+        .await;
+        assert_eq!(results[0].as_ref().unwrap(), &10);
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap(), &30);
+    }
+}
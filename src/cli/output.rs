@@ -0,0 +1,175 @@
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{
+    gamebanana::models::{file::GBFile, search_result::GBSearchEntry, submitter::GBSubmitter},
+    modz::Mod,
+};
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum OutputFormat {
+    /// Human readable text
+    #[default]
+    Table,
+    /// Full records, pretty printed
+    Json,
+    /// Header line then one line per record
+    Csv,
+}
+
+/// Flat view of a record used for csv output
+pub trait Tabular {
+    fn headers() -> Vec<&'static str>;
+    fn fields(&self) -> Vec<String>;
+}
+
+/// Prints records as json or csv, hands over to `table` for the default format
+pub fn print_records<T: Serialize + Tabular>(
+    records: &[T],
+    format: OutputFormat,
+    table: impl Fn(&T),
+) {
+    match format {
+        OutputFormat::Table => records.iter().for_each(table),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(records).expect("Records are always serializable")
+        ),
+        OutputFormat::Csv => {
+            println!("{}", T::headers().join(","));
+            for record in records {
+                let fields: Vec<String> = record.fields().iter().map(|f| csv_field(f)).collect();
+                println!("{}", fields.join(","));
+            }
+        }
+    }
+}
+
+fn submitter_name(submitter: &Option<GBSubmitter>) -> String {
+    submitter
+        .as_ref()
+        .map(|s| s.name.clone())
+        .unwrap_or_default()
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+impl Tabular for Mod {
+    fn headers() -> Vec<&'static str> {
+        vec![
+            "id",
+            "name",
+            "character",
+            "variant",
+            "staged",
+            "is_nsfw",
+            "path",
+            "description",
+            "submitter",
+            "super_category",
+            "date_added",
+            "date_updated",
+            "profile_url",
+        ]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.name.clone(),
+            self.character.clone(),
+            self.variant.clone(),
+            self.staged.to_string(),
+            self.is_nsfw.to_string(),
+            self.path().display().to_string(),
+            self.description.clone(),
+            submitter_name(&self.submitter),
+            self.super_category.clone().unwrap_or_default(),
+            self.date_added.to_string(),
+            self.date_updated.to_string(),
+            self.profile_url.clone(),
+        ]
+    }
+}
+
+impl Tabular for GBSearchEntry {
+    fn headers() -> Vec<&'static str> {
+        vec![
+            "id",
+            "name",
+            "category",
+            "views",
+            "likes",
+            "downloads",
+            "date_added",
+            "date_updated",
+            "is_nsfw",
+            "description",
+            "submitter",
+            "profile_url",
+        ]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.row.to_string(),
+            self.name.clone(),
+            self.category.name.clone(),
+            self.view_count.to_string(),
+            self.like_count.to_string(),
+            self.download_count.to_string(),
+            self.date_added.to_string(),
+            self.date_updated.to_string(),
+            self.is_nsfw.to_string(),
+            self.description.clone(),
+            submitter_name(&self.submitter),
+            self.profile_url.clone(),
+        ]
+    }
+}
+
+impl Tabular for GBFile {
+    fn headers() -> Vec<&'static str> {
+        vec![
+            "id",
+            "file",
+            "filesize",
+            "downloads",
+            "date_added",
+            "contains_exe",
+            "description",
+            "download_url",
+        ]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.row.to_string(),
+            self.file.clone(),
+            self.filesize.to_string(),
+            self.download_count.to_string(),
+            self.date_added.to_string(),
+            self.contains_exe.to_string(),
+            self.description.clone(),
+            self.download_url.clone(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::csv_field;
+
+    #[test]
+    fn csv_fields_quote() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,201 @@
+use std::pin::pin;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use futures::StreamExt;
+
+use crate::{
+    error::GGModError,
+    gamebanana::{
+        builder::{FeedFilter, SearchBuilder, SearchFilter, TypeFilter},
+        models::category::GBModCategory,
+        parse_date,
+        query::SearchQuery,
+        search::PAGE_DELAY,
+        GAME_ID, ROOT_CATEGORY,
+    },
+};
+
+use super::{print_records, OutputFormat, Tabular};
+
+#[derive(Args, Debug, Clone)]
+pub struct SearchArgs {
+    /// Number of results per page
+    #[arg(short, long, default_value_t = 15)]
+    pub size: usize,
+
+    /// Section to search in
+    #[arg(short = 't', long = "type", value_enum, default_value_t = TypeFilter::Skin)]
+    pub section: TypeFilter,
+
+    /// Category id or name, see `ggmod categories`
+    #[arg(short, long)]
+    pub category: Option<String>,
+
+    /// Only mods by this submitter, id or part of their name
+    #[arg(long, value_name = "ID|NAME")]
+    pub submitter: Option<String>,
+
+    /// Only mods added or updated on or after this day
+    #[arg(long, value_name = "YYYY-MM-DD", value_parser = date_arg)]
+    pub updated_since: Option<usize>,
+
+    /// Only mods downloaded at least this many times
+    #[arg(long, value_name = "COUNT")]
+    pub min_downloads: Option<usize>,
+
+    /// Include NSFW mods
+    #[arg(long)]
+    pub nsfw: bool,
+
+    /// Result ordering, featured by default
+    #[arg(long, value_enum, conflicts_with_all = ["featured", "popular", "recent"])]
+    pub sort: Option<FeedFilter>,
+
+    /// Featured mods first, the default, same as --sort featured
+    #[arg(short, long, conflicts_with_all = ["popular", "recent"])]
+    pub featured: bool,
+
+    /// Sort by popularity, same as --sort popular
+    #[arg(short, long, conflicts_with = "recent")]
+    pub popular: bool,
+
+    /// Sort by time, same as --sort recent
+    #[arg(short, long)]
+    pub recent: bool,
+
+    /// Keep fetching from PAGE until the last page
+    #[arg(long)]
+    pub all_pages: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+
+    /// Page no. starting from 1
+    pub page: usize,
+
+    /// Search by name
+    pub name: Option<String>,
+}
+
+impl SearchArgs {
+    fn sort(&self) -> FeedFilter {
+        match (&self.sort, self.popular, self.recent) {
+            (Some(sort), _, _) => sort.clone(),
+            (None, true, _) => FeedFilter::Popular,
+            (None, _, true) => FeedFilter::Recent,
+            _ => FeedFilter::Featured,
+        }
+    }
+}
+
+pub async fn search(args: &SearchArgs) -> Result<()> {
+    let category = match &args.category {
+        Some(cat) => Some(resolve_category(cat).await?),
+        None => None,
+    };
+    let query = SearchQuery::new()
+        .name(args.name.as_deref())
+        .category(category)
+        .submitter(args.submitter.as_deref())
+        .updated_since(args.updated_since)
+        .min_downloads(args.min_downloads);
+    let search = SearchBuilder::new()
+        .per_page(args.size)
+        .of_type(args.section.clone())
+        .with_sort(args.sort())
+        .by_search(SearchFilter::Game { game_id: GAME_ID })
+        .with_query(query)
+        .nsfw(args.nsfw)
+        .build();
+    let (entries, info) = if args.all_pages {
+        let mut pages = pin!(search.pages(args.page, PAGE_DELAY));
+        let (mut entries, mut info) = (Vec::new(), None);
+        while let Some(page) = pages.next().await {
+            let page = page.context("Couldn't get search results")?;
+            info = Some(page.info);
+            entries.extend(page.entries);
+        }
+        (entries, info)
+    } else {
+        let page = search
+            .read_page(args.page)
+            .await
+            .context("Couldn't get search results")?;
+        (page.entries, Some(page.info))
+    };
+    print_records(&entries, args.format, |entry| {
+        let name: String = entry.name.chars().take(35).collect();
+        let desc: String = entry.description.chars().take(50).collect();
+        let views = entry.view_count;
+        println!("{name:<35} - {desc:<50} :: {views} views");
+    });
+    if let (OutputFormat::Table, Some(info)) = (args.format, info) {
+        match info.total {
+            Some(total) => println!("{info}, {total} results"),
+            None => println!("{info}"),
+        }
+    }
+    Ok(())
+}
+
+fn date_arg(s: &str) -> Result<usize, String> {
+    parse_date(s).ok_or_else(|| format!("{s:?} isn't a YYYY-MM-DD date"))
+}
+
+/// Numbers are taken as category ids as is, anything else is looked up by name
+async fn resolve_category(cat: &str) -> Result<usize> {
+    if let Ok(id) = cat.trim().parse::<usize>() {
+        return Ok(id);
+    }
+    GBModCategory::tree(ROOT_CATEGORY)
+        .await
+        .context("Couldn't get categories")?
+        .into_iter()
+        .find(|(_, c)| c.name.eq_ignore_ascii_case(cat.trim()))
+        .map(|(_, c)| c.row)
+        .ok_or_else(|| {
+            let msg = format!("No category named {cat:?}, see `ggmod categories`");
+            GGModError::Usage(msg).into()
+        })
+}
+
+pub async fn categories(format: OutputFormat) -> Result<()> {
+    let tree = GBModCategory::tree(ROOT_CATEGORY)
+        .await
+        .context("Couldn't get categories")?;
+    let records: Vec<CategoryRecord> = tree
+        .into_iter()
+        .map(|(depth, category)| CategoryRecord { depth, category })
+        .collect();
+    print_records(&records, format, |r| {
+        let indent = "  ".repeat(r.depth);
+        let c = &r.category;
+        println!("{indent}{:<8} {} ({} mods)", c.row, c.name, c.item_count);
+    });
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+struct CategoryRecord {
+    depth: usize,
+    #[serde(flatten)]
+    category: GBModCategory,
+}
+
+impl Tabular for CategoryRecord {
+    fn headers() -> Vec<&'static str> {
+        vec!["id", "name", "depth", "items", "subcategories"]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.category.row.to_string(),
+            self.category.name.clone(),
+            self.depth.to_string(),
+            self.category.item_count.to_string(),
+            self.category.category_count.to_string(),
+        ]
+    }
+}
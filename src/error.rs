@@ -0,0 +1,156 @@
+use std::{fmt, io, path::PathBuf};
+
+use log::error;
+
+/// Errors we want to tell apart when reporting to the user, anything else that bubbles
+/// up through anyhow is classified by looking at its source chain
+#[derive(Debug)]
+pub enum GGModError {
+    Network(String),
+    Offline(String),
+    Parse(String),
+    Filesystem(String),
+    GameNotFound(String),
+    Registry { path: PathBuf, reason: String },
+    Usage(String),
+    Batch { failed: usize, total: usize },
+}
+
+impl fmt::Display for GGModError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GGModError::Network(s) => write!(f, "network error: {s}"),
+            GGModError::Offline(s) => write!(f, "not cached and running offline: {s}"),
+            GGModError::Parse(s) => write!(f, "unexpected response: {s}"),
+            GGModError::Filesystem(s) => write!(f, "filesystem error: {s}"),
+            GGModError::GameNotFound(s) => write!(f, "couldn't find Guilty Gear Strive: {s}"),
+            GGModError::Registry { path, reason } => {
+                write!(f, "registry at {} is unreadable: {reason}", path.display())
+            }
+            GGModError::Usage(s) => write!(f, "{s}"),
+            GGModError::Batch { failed, total } => {
+                write!(f, "{failed} of {total} operations failed")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GGModError {}
+
+/// What went wrong in broad strokes, decides exit code and hint
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorKind {
+    Other,
+    Usage,
+    Network,
+    Parse,
+    Filesystem,
+    GameNotFound,
+    Registry,
+    Batch,
+    Offline,
+}
+
+impl ErrorKind {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ErrorKind::Other => 1,
+            ErrorKind::Usage => 2,
+            ErrorKind::Network => 3,
+            ErrorKind::Parse => 4,
+            ErrorKind::Filesystem => 5,
+            ErrorKind::GameNotFound => 6,
+            ErrorKind::Registry => 7,
+            ErrorKind::Batch => 8,
+            ErrorKind::Offline => 9,
+        }
+    }
+
+    /// GameBanana couldn't be reached, either for real or because of --offline
+    pub fn is_unreachable(&self) -> bool {
+        matches!(self, ErrorKind::Network | ErrorKind::Offline)
+    }
+
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            ErrorKind::Network => Some("check your connection, GameBanana might also be down"),
+            ErrorKind::Parse => {
+                Some("GameBanana answered with something unexpected, the mod may be gone")
+            }
+            ErrorKind::Filesystem => Some("check permissions and free space of the ggmod dirs"),
+            ErrorKind::GameNotFound => {
+                Some("ggmod looks for a Steam install of GGST, is the game installed?")
+            }
+            ErrorKind::Registry => {
+                Some("fix the file by hand or move it away to start with an empty collection")
+            }
+            ErrorKind::Batch => Some("see the summary above for what failed"),
+            ErrorKind::Offline => Some("run without --offline to fetch it"),
+            ErrorKind::Other | ErrorKind::Usage => None,
+        }
+    }
+}
+
+impl From<&GGModError> for ErrorKind {
+    fn from(value: &GGModError) -> Self {
+        match value {
+            GGModError::Network(_) => ErrorKind::Network,
+            GGModError::Offline(_) => ErrorKind::Offline,
+            GGModError::Parse(_) => ErrorKind::Parse,
+            GGModError::Filesystem(_) => ErrorKind::Filesystem,
+            GGModError::GameNotFound(_) => ErrorKind::GameNotFound,
+            GGModError::Registry { .. } => ErrorKind::Registry,
+            GGModError::Usage(_) => ErrorKind::Usage,
+            GGModError::Batch { .. } => ErrorKind::Batch,
+        }
+    }
+}
+
+pub fn classify(err: &anyhow::Error) -> ErrorKind {
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<GGModError>() {
+            return e.into();
+        } else if cause.is::<reqwest::Error>() {
+            return ErrorKind::Network;
+        } else if cause.is::<serde_json::Error>() {
+            return ErrorKind::Parse;
+        } else if cause.is::<io::Error>() {
+            return ErrorKind::Filesystem;
+        }
+    }
+    ErrorKind::Other
+}
+
+/// Prints the error for humans and returns the exit code to use
+pub fn report(err: &anyhow::Error, verbose: u8) -> i32 {
+    let kind = classify(err);
+    error!("{err:?}");
+    eprintln!("error: {err:#}");
+    if let Some(hint) = kind.hint() {
+        eprintln!("hint: {hint}");
+    }
+    if verbose > 0 {
+        eprintln!("\n{err:?}");
+    }
+    kind.exit_code()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn errors_classify() {
+        let io = io::Error::new(io::ErrorKind::PermissionDenied, "nope");
+        let err = anyhow::Error::from(io).context("Couldn't stage");
+        assert_eq!(classify(&err), ErrorKind::Filesystem);
+        let err = serde_json::from_str::<usize>("x")
+            .context("Couldn't read page")
+            .unwrap_err();
+        assert_eq!(classify(&err), ErrorKind::Parse);
+        let err = anyhow::Error::from(GGModError::GameNotFound(String::from("no steam")));
+        assert_eq!(classify(&err).exit_code(), 6);
+        assert_eq!(classify(&anyhow::anyhow!("?")), ErrorKind::Other);
+    }
+}
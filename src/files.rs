@@ -8,6 +8,11 @@ use std::{
 
 pub const SUBDIR_NAME: &str = "ggmod";
 pub const REGISTRY_FN: &str = "registry.json";
+pub const MODS_DIR: &str = "mods";
+pub const MEDIA_DIR: &str = "media";
+/// Start of the working dirs downloads extract into before moving into place
+pub const SCRATCH_PREFIX: &str = ".part-";
+pub const LOG_FN: &str = "ggmod.log";
 
 pub fn not_found(s: &str) -> io::Error {
     io::Error::new(io::ErrorKind::NotFound, s)
@@ -27,6 +32,41 @@ pub fn download_path() -> Result<path::PathBuf> {
     Ok(dl_path)
 }
 
+/// Cached GameBanana API responses, safe to delete at any time
+pub fn http_cache_path() -> Result<path::PathBuf> {
+    let path = ggmod_root()?.cache_dir().join("http");
+    fs::DirBuilder::new().recursive(true).create(&path)?;
+    Ok(path)
+}
+
+/// State dir where the platform has one (Linux), local data dir otherwise
+pub fn log_path() -> Result<path::PathBuf> {
+    let proj_root = ggmod_root()?;
+    let dir = proj_root
+        .state_dir()
+        .unwrap_or(proj_root.data_local_dir())
+        .join("logs");
+    fs::DirBuilder::new().recursive(true).create(&dir)?;
+    Ok(dir.join(LOG_FN))
+}
+
+/// Archive and extraction target for a single file of a mod, mods/<mod id>/<file id>
+pub fn mod_cache_path(mod_id: usize, file_id: usize) -> Result<path::PathBuf> {
+    let path = download_path()?
+        .join(MODS_DIR)
+        .join(mod_id.to_string())
+        .join(file_id.to_string());
+    fs::DirBuilder::new().recursive(true).create(&path)?;
+    Ok(path)
+}
+
+/// Preview images for a mod, media/<mod id>
+pub fn media_cache_path(mod_id: usize) -> Result<path::PathBuf> {
+    let path = download_path()?.join(MEDIA_DIR).join(mod_id.to_string());
+    fs::DirBuilder::new().recursive(true).create(&path)?;
+    Ok(path)
+}
+
 pub fn steam_root() -> Result<path::PathBuf> {
     // TODO: This will probably need new entries + replace exists call
     let steamroot = [
@@ -54,7 +94,7 @@ pub fn ggst_path() -> Result<path::PathBuf> {
         .join("Paks")
         .join("~mods");
     fs::DirBuilder::new().recursive(true).create(&path)?;
-    trace!("Found path {:?} for steam root", path);
+    trace!(path:?; "Found GGST mod path under steam root");
     Ok(path)
     // hi
 }
@@ -62,7 +102,7 @@ pub fn ggst_path() -> Result<path::PathBuf> {
 pub fn game_sig_file() -> Result<path::PathBuf> {
     let path = ggst_path()?
         .parent()
-        .unwrap()
+        .ok_or(not_found("GGST mod folder has no parent"))?
         .join("pakchunk0-WindowsNoEditor.sig");
     if path.exists() && path.is_file() {
         Ok(path)
@@ -84,18 +124,13 @@ pub fn ensure_sig_file(path: &path::Path) -> Result<()> {
         "Have sig in {path:?}: {has_sig}, have name: {}",
         name.is_some()
     );
-    if !has_sig && name.is_some() {
-        trace!(
-            "Copy {:?} to {:?}",
-            game_sig_file()?,
-            name.as_ref().unwrap().as_ref().unwrap().path()
-        );
-        fs::copy(
-            game_sig_file()?,
-            name.unwrap().unwrap().path().with_extension("sig"),
-        )?;
-    } else if !has_sig && name.is_none() {
-        warn!("Have no .sig in {path:?} but also no .pak");
+    match name {
+        Some(Ok(pak)) if !has_sig => {
+            trace!(from:? = game_sig_file()?, to:? = pak.path(); "Copy game sig");
+            fs::copy(game_sig_file()?, pak.path().with_extension("sig"))?;
+        }
+        None if !has_sig => warn!(path:?; "Have no .sig but also no .pak"),
+        _ => (),
     }
     Ok(())
 }
@@ -134,6 +169,15 @@ mod test {
         assert!(path.exists() && path.is_file());
     }
 
+    #[test]
+    fn cache_paths_nest() {
+        let root = download_path().unwrap();
+        let file = mod_cache_path(1, 2).unwrap();
+        let media = media_cache_path(1).unwrap();
+        assert_eq!(file, root.join(MODS_DIR).join("1").join("2"));
+        assert_eq!(media, root.join(MEDIA_DIR).join("1"));
+    }
+
     #[test]
     fn ggmod_root_works() {
         ggmod_root().unwrap();
@@ -1,12 +1,25 @@
 mod util;
 use util::*;
+pub use util::{decode_entities, format_date, html_to_text, human_size, lenient, parse_date};
 pub mod models {
+    pub mod alternate_source;
     pub mod category;
     pub mod file;
     pub mod game;
     pub mod modpage;
     pub mod preview;
     pub mod search_result;
+    pub mod submitter;
+    pub mod tag;
+    pub mod update;
 }
+pub mod api;
 pub mod builder;
+pub mod http;
+pub mod query;
 pub mod search;
+
+/// GameBanana's id for Guilty Gear -Strive-
+pub const GAME_ID: usize = 11534;
+/// Root of the GGST mod category tree, characters hang off this one
+pub const ROOT_CATEGORY: usize = 12914;
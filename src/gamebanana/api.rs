@@ -0,0 +1,86 @@
+use anyhow::Result;
+use log::trace;
+use serde::{de::DeserializeOwned, Deserialize};
+
+use super::{http, to_human};
+
+/// Everything goes through apiv11, older versions are missing fields and endpoints
+pub const API_BASE: &str = "https://gamebanana.com/apiv11/";
+
+pub fn url(path: &str) -> String {
+    format!("{API_BASE}{path}")
+}
+
+/// `_aMetadata` of listing endpoints
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Metadata {
+    pub record_count: Option<usize>,
+    pub perpage: Option<usize>,
+    pub is_complete: Option<bool>,
+}
+
+/// `{"_aMetadata": .., "_aRecords": [..]}`, what every apiv11 listing answers with
+#[derive(Deserialize, Debug, Clone)]
+pub struct Envelope<T> {
+    #[serde(default)]
+    pub metadata: Metadata,
+    pub records: Vec<T>,
+}
+
+/// A few listings (categories) skip the envelope and answer with the records
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Listing<T> {
+    Paged(Envelope<T>),
+    Bare(Vec<T>),
+}
+
+/// GETs `url` through the cache and deserializes it with human field names
+pub async fn get<T: DeserializeOwned>(url: &str) -> Result<T> {
+    let resp = http::get_text(url).await?;
+    trace!(url:%; "resp: {resp}");
+    let conv = to_human(&resp)?;
+    Ok(serde_json::from_str(&conv)?)
+}
+
+pub async fn get_list<T: DeserializeOwned>(url: &str) -> Result<Envelope<T>> {
+    Ok(match get::<Listing<T>>(url).await? {
+        Listing::Paged(envelope) => envelope,
+        Listing::Bare(records) => Envelope {
+            metadata: Metadata {
+                record_count: Some(records.len()),
+                is_complete: Some(true),
+                ..Default::default()
+            },
+            records,
+        },
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(json: &str) -> Envelope<usize> {
+        match serde_json::from_str(&to_human(json).unwrap()).unwrap() {
+            Listing::Paged(envelope) => envelope,
+            Listing::Bare(records) => Envelope {
+                metadata: Metadata::default(),
+                records,
+            },
+        }
+    }
+
+    #[test]
+    fn envelopes_parse() {
+        let paged = parse(
+            r#"{"_aMetadata": {"_nRecordCount": 42, "_bIsComplete": false, "_nPerpage": 2},
+                "_aRecords": [1, 2]}"#,
+        );
+        assert_eq!(paged.metadata.record_count, Some(42));
+        assert_eq!(paged.metadata.perpage, Some(2));
+        assert_eq!(paged.records, vec![1, 2]);
+        assert_eq!(parse("[3]").records, vec![3]);
+    }
+}
@@ -1,64 +1,48 @@
+use clap::ValueEnum;
+use reqwest::Url;
 use strum::EnumIter;
 
-use super::search::Search;
+use super::{api, query::SearchQuery, search::Search, GAME_ID};
 
-#[derive(EnumIter, Clone, Debug, PartialEq)]
+#[derive(EnumIter, ValueEnum, Clone, Debug, PartialEq)]
 pub enum TypeFilter {
     Skin,
     Sound,
+    #[value(name = "wip")]
     WiP,
 }
 
+/// Shorthands for the common queries, see `SearchQuery` for combining them
 pub enum SearchFilter<'a> {
     Name { search: &'a str, game_id: usize },
     Game { game_id: usize },
     Category { cat_id: usize },
 }
 
-#[derive(EnumIter, Clone, Debug, PartialEq)]
+#[derive(EnumIter, ValueEnum, Clone, Debug, PartialEq)]
 pub enum FeedFilter {
     Recent,
     Popular,
     Featured,
 }
 
-pub struct SearchBuilder<'a> {
+pub struct SearchBuilder {
     mod_type: TypeFilter,
-    search: SearchFilter<'a>,
+    game_id: Option<usize>,
+    query: SearchQuery,
     feed: FeedFilter,
     per_page: usize,
-    category: Option<usize>,
     nsfw: bool,
 }
 
-const PROPS: [&str; 17] = [
-    "_sName",
-    "_sModelName",
-    "_idRow",
-    "_aSubmitter",
-    "_tsDateUpdated",
-    "_tsDateAdded",
-    "_aPreviewMedia",
-    "_sText",
-    "_sDescription",
-    "_aCategory",
-    "_aRootCategory",
-    "_aGame",
-    "_nViewCount",
-    "_nLikeCount",
-    "_nDownloadCount",
-    "_bIsNsfw",
-    "_aAlternateFileSources",
-];
-
-impl<'a> SearchBuilder<'a> {
-    pub fn new() -> SearchBuilder<'a> {
+impl SearchBuilder {
+    pub fn new() -> SearchBuilder {
         SearchBuilder {
             mod_type: TypeFilter::Skin,
-            search: SearchFilter::Game { game_id: 11534 },
+            game_id: Some(GAME_ID),
+            query: SearchQuery::default(),
             feed: FeedFilter::Featured,
             per_page: 30,
-            category: None,
             nsfw: false,
         }
     }
@@ -69,12 +53,26 @@ impl<'a> SearchBuilder<'a> {
     }
 
     pub fn of_category(mut self, cat_id: Option<usize>) -> Self {
-        self.category = cat_id;
+        self.query = self.query.category(cat_id);
         self
     }
 
-    pub fn by_search(mut self, search: SearchFilter<'a>) -> Self {
-        self.search = search;
+    /// Adds to the query instead of replacing it, so name and category can be combined
+    pub fn by_search(mut self, search: SearchFilter<'_>) -> Self {
+        match search {
+            SearchFilter::Name { search, game_id } => {
+                self.query = self.query.name(Some(search));
+                self.game_id = Some(game_id);
+            }
+            SearchFilter::Game { game_id } => self.game_id = Some(game_id),
+            SearchFilter::Category { cat_id } => self.query = self.query.category(Some(cat_id)),
+        }
+        self
+    }
+
+    /// Replaces everything set through `by_search` and `of_category`
+    pub fn with_query(mut self, query: SearchQuery) -> Self {
+        self.query = query;
         self
     }
 
@@ -93,49 +91,144 @@ impl<'a> SearchBuilder<'a> {
         self
     }
 
-    pub fn build(self) -> Search {
-        let mut part = String::new();
-        let per_page = self.per_page;
-        match self.mod_type {
-            TypeFilter::Skin => part.push_str("Mod/"),
-            TypeFilter::Sound => part.push_str("Sound/"),
-            TypeFilter::WiP => part.push_str("Wip/"),
-        }
-        match self.search {
-            SearchFilter::Category { cat_id } => {
-                part.push_str(&format!("ByCategory?_aCategoryRowIds[]={cat_id}&"))
-            }
-            SearchFilter::Name { search, game_id } => {
-                part.push_str(&format!("ByName?_sName=*{search}*&_idGameRow={game_id}&"))
-            }
-            SearchFilter::Game { game_id } => {
-                part.push_str(&format!("ByGame?_aGameRowIds[]={game_id}&"))
-            }
+    /// Index url of the first page, pages are added by `Search`
+    pub fn url(&self) -> Url {
+        let model = match self.mod_type {
+            TypeFilter::Skin => "Mod",
+            TypeFilter::Sound => "Sound",
+            TypeFilter::WiP => "Wip",
         };
-        part.push_str(&format!(
-            "_csvProperties={}&_nPerpage={}",
-            PROPS.join(","),
-            per_page
-        ));
-        if !self.nsfw {
-            part.push_str("&_aArgs[]=_sbIsNsfw = false");
+        let mut url = Url::parse(&api::url(&format!("{model}/Index"))).expect("Base url is valid");
+        let mut pairs = url.query_pairs_mut();
+        pairs.append_pair("_nPerpage", &self.per_page.to_string());
+        if let Some(game_id) = self.game_id {
+            pairs.append_pair("_aFilters[Generic_Game]", &game_id.to_string());
         }
-        match self.feed {
-            FeedFilter::Popular => part.push_str("&_sOrderBy=_nDownloadCount,DESC"),
-            FeedFilter::Featured => {
-                part.push_str("&_aArgs[]=_sbWasFeatured = true& _sOrderBy=_tsDateAdded,DESC")
-            }
-            FeedFilter::Recent => part.push_str("&_sOrderBy=_tsDateUpdated,DESC"),
+        if let Some(name) = &self.query.name {
+            pairs.append_pair("_aFilters[Generic_Name]", name);
+        }
+        if let Some(cat_id) = self.query.category {
+            pairs.append_pair("_aFilters[Generic_Category]", &cat_id.to_string());
+        }
+        if !self.nsfw {
+            pairs.append_pair("_aFilters[Generic_HasContentRatings]", "false");
         }
-        if let Some(id) = self.category {
-            part.push_str(&format!("&_aCategoryRowIds[]={id}"))
+        if let FeedFilter::Featured = self.feed {
+            pairs.append_pair("_aFilters[Generic_WasFeatured]", "true");
         }
-        Search::base(&part)
+        let sort = match self.feed {
+            FeedFilter::Popular => "Generic_MostDownloaded",
+            FeedFilter::Featured => "Generic_Newest",
+            FeedFilter::Recent => "Generic_LatestUpdated",
+        };
+        pairs.append_pair("_sSort", sort);
+        drop(pairs);
+        url
+    }
+
+    pub fn build(self) -> Search {
+        Search::from_url(self.url())
+            .with_page_size(self.per_page)
+            .with_filter(self.query)
     }
 }
 
-impl Default for SearchBuilder<'_> {
+impl Default for SearchBuilder {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    fn pairs(url: &Url) -> HashMap<String, String> {
+        url.query_pairs().into_owned().collect()
+    }
+
+    #[test]
+    fn urls_exact() {
+        let url = SearchBuilder::new()
+            .per_page(15)
+            .with_sort(FeedFilter::Featured)
+            .by_search(SearchFilter::Name {
+                search: "Sol & Ky #1",
+                game_id: GAME_ID,
+            })
+            .url();
+        assert_eq!(
+            url.as_str(),
+            "https://gamebanana.com/apiv11/Mod/Index?_nPerpage=15\
+             &_aFilters%5BGeneric_Game%5D=11534\
+             &_aFilters%5BGeneric_Name%5D=Sol+%26+Ky+%231\
+             &_aFilters%5BGeneric_HasContentRatings%5D=false\
+             &_aFilters%5BGeneric_WasFeatured%5D=true\
+             &_sSort=Generic_Newest"
+        );
+        let url = SearchBuilder::new()
+            .nsfw(true)
+            .by_search(SearchFilter::Name {
+                search: "ソル badguy",
+                game_id: GAME_ID,
+            })
+            .url();
+        assert_eq!(pairs(&url)["_aFilters[Generic_Name]"], "ソル badguy");
+        assert!(!pairs(&url).contains_key("_aFilters[Generic_HasContentRatings]"));
+        assert!(!url.as_str().contains(' '));
+    }
+
+    #[test]
+    fn urls_cover_all_filters() {
+        for mod_type in TypeFilter::iter() {
+            for feed in FeedFilter::iter() {
+                let filters = [
+                    SearchFilter::Name {
+                        search: "red sol",
+                        game_id: 1,
+                    },
+                    SearchFilter::Game { game_id: 2 },
+                    SearchFilter::Category { cat_id: 3 },
+                ];
+                for filter in filters {
+                    let expected_filter = match &filter {
+                        SearchFilter::Name { .. } => ("_aFilters[Generic_Name]", "red sol"),
+                        SearchFilter::Game { .. } => ("_aFilters[Generic_Game]", "2"),
+                        SearchFilter::Category { .. } => ("_aFilters[Generic_Category]", "3"),
+                    };
+                    let url = SearchBuilder::new()
+                        .of_type(mod_type.clone())
+                        .with_sort(feed.clone())
+                        .by_search(filter)
+                        .url();
+                    let path = match mod_type {
+                        TypeFilter::Skin => "/apiv11/Mod/Index",
+                        TypeFilter::Sound => "/apiv11/Sound/Index",
+                        TypeFilter::WiP => "/apiv11/Wip/Index",
+                    };
+                    let (sort, featured) = match feed {
+                        FeedFilter::Recent => ("Generic_LatestUpdated", None),
+                        FeedFilter::Popular => ("Generic_MostDownloaded", None),
+                        FeedFilter::Featured => ("Generic_Newest", Some("true")),
+                    };
+                    let pairs = pairs(&url);
+                    assert_eq!(url.path(), path);
+                    assert_eq!(pairs[expected_filter.0], expected_filter.1);
+                    assert_eq!(pairs["_sSort"], sort);
+                    assert_eq!(pairs["_nPerpage"], "30");
+                    assert_eq!(pairs["_aFilters[Generic_HasContentRatings]"], "false");
+                    assert_eq!(
+                        pairs
+                            .get("_aFilters[Generic_WasFeatured]")
+                            .map(String::as_str),
+                        featured
+                    );
+                    assert_eq!(url.query_pairs().count(), pairs.len());
+                }
+            }
+        }
+    }
+}
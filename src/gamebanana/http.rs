@@ -0,0 +1,411 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use log::{debug, info, trace, warn};
+use reqwest::{
+    header::{HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER},
+    Request, RequestBuilder, Response, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{error::GGModError, http_cache_path};
+
+/// How long a cached response is used as is before asking GameBanana again
+pub const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+/// Longest Retry-After we go along with, a server asking for more gets this
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+static CONFIG: OnceLock<HttpConfig> = OnceLock::new();
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+static HOSTS: OnceLock<Mutex<HashMap<String, Arc<Semaphore>>>> = OnceLock::new();
+
+/// How every request to GameBanana (or wherever files are hosted) is sent
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    pub user_agent: String,
+    /// Whole request including the body, only for API calls since archives can take
+    /// as long as they need
+    pub timeout: Duration,
+    /// Longest wait for the next bytes of a body, catches stalled downloads
+    pub read_timeout: Duration,
+    pub connect_timeout: Duration,
+    /// Requests in flight per host, the rest wait their turn
+    pub per_host: usize,
+    /// Retries after the first attempt on 429, 5xx and timeouts
+    pub retries: u32,
+    /// First backoff, doubled on every retry
+    pub backoff: Duration,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: format!("ggmod/{}", env!("CARGO_PKG_VERSION")),
+            timeout: Duration::from_secs(60),
+            read_timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+            per_host: 4,
+            retries: 3,
+            backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Has to happen before the first request, later calls are ignored
+pub fn configure(config: HttpConfig) {
+    if CONFIG.set(config).is_err() {
+        warn!("HTTP already configured, ignoring");
+    }
+}
+
+fn config() -> &'static HttpConfig {
+    CONFIG.get_or_init(HttpConfig::default)
+}
+
+/// Only serve what is already cached, every request that would hit the network fails
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Call before downloading anything that doesn't go through `get_text`
+pub fn ensure_online(url: &str) -> Result<()> {
+    if is_offline() {
+        Err(GGModError::Offline(url.to_string()).into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Validates a user supplied User-Agent, anything that can't be a header value would
+/// make building the client fail
+pub fn user_agent_arg(s: &str) -> Result<String, String> {
+    HeaderValue::from_str(s)
+        .map(|_| s.to_string())
+        .map_err(|_| format!("{s:?} isn't a valid User-Agent header"))
+}
+
+pub fn client() -> &'static reqwest::Client {
+    CLIENT.get_or_init(|| {
+        let config = config();
+        reqwest::Client::builder()
+            .user_agent(&config.user_agent)
+            .read_timeout(config.read_timeout)
+            .connect_timeout(config.connect_timeout)
+            .build()
+            .expect("User-Agent is checked by user_agent_arg")
+    })
+}
+
+fn host_permits(host: &str) -> Arc<Semaphore> {
+    let hosts = HOSTS.get_or_init(Default::default);
+    let mut hosts = hosts.lock().unwrap_or_else(|e| e.into_inner());
+    hosts
+        .entry(host.to_string())
+        .or_insert_with(|| Arc::new(Semaphore::new(config().per_host.max(1))))
+        .clone()
+}
+
+fn is_transient(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Retry-After when given in seconds
+fn retry_after(resp: &Response) -> Option<Duration> {
+    let secs = resp
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(secs))
+}
+
+/// Honours Retry-After up to `MAX_RETRY_AFTER`, otherwise doubles per attempt
+fn backoff(attempt: u32, retry_after: Option<Duration>) -> Duration {
+    match retry_after {
+        Some(delay) => delay.min(MAX_RETRY_AFTER),
+        None => config().backoff * 2u32.saturating_pow(attempt),
+    }
+}
+
+/// Sends through the per-host limit, retrying transient failures. The permit is
+/// handed back so callers can keep the slot while reading the body, it's let go while
+/// backing off so one throttled request doesn't hold up the rest
+async fn execute(req: RequestBuilder) -> Result<(Response, OwnedSemaphorePermit)> {
+    let req: Request = req.build()?;
+    let url = req.url().to_string();
+    ensure_online(&url)?;
+    let host = req.url().host_str().unwrap_or_default().to_string();
+    let mut attempt = 0;
+    loop {
+        let permit = host_permits(&host)
+            .acquire_owned()
+            .await
+            .expect("Semaphores are never closed");
+        let this_try = req.try_clone().context("Request can't be retried")?;
+        let res = client().execute(this_try).await;
+        let retry = match &res {
+            Ok(resp) => is_transient(resp.status()),
+            Err(e) => e.is_timeout() || e.is_connect(),
+        };
+        if !retry || attempt >= config().retries {
+            return Ok((res?, permit));
+        }
+        let delay = backoff(attempt, res.as_ref().ok().and_then(retry_after));
+        match &res {
+            Ok(resp) => warn!(url:%, status:% = resp.status(), attempt; "Retrying in {delay:?}"),
+            Err(e) => warn!(url:%, attempt; "Retrying in {delay:?}: {e}"),
+        }
+        drop(permit);
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// GET without caching, for archives and images
+pub async fn get_bytes(url: &str) -> Result<Bytes> {
+    get_bytes_tracked(url, &Progress::default()).await
+}
+
+/// Same as `get_bytes`, counting bytes into `progress` as they come in
+pub async fn get_bytes_tracked(url: &str, progress: &Progress) -> Result<Bytes> {
+    let (resp, _permit) = execute(client().get(url)).await?;
+    let mut resp = resp.error_for_status()?;
+    progress.start(resp.content_length());
+    let mut body = Vec::with_capacity(resp.content_length().unwrap_or(0) as usize);
+    while let Some(chunk) = resp.chunk().await? {
+        progress.advance(chunk.len() as u64);
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body.into())
+}
+
+/// How far along a download is, clones share the same counters
+#[derive(Clone, Debug, Default)]
+pub struct Progress(Arc<Counters>);
+
+#[derive(Debug, Default)]
+struct Counters {
+    done: AtomicU64,
+    /// 0 while unknown
+    total: AtomicU64,
+}
+
+impl Progress {
+    fn start(&self, total: Option<u64>) {
+        self.0.done.store(0, Ordering::Relaxed);
+        self.0.total.store(total.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    fn advance(&self, bytes: u64) {
+        self.0.done.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn done(&self) -> u64 {
+        self.0.done.load(Ordering::Relaxed)
+    }
+
+    pub fn total(&self) -> Option<u64> {
+        Some(self.0.total.load(Ordering::Relaxed)).filter(|t| *t > 0)
+    }
+
+    /// Between 0 and 1, None when the server didn't say how big the body is
+    pub fn ratio(&self) -> Option<f64> {
+        self.total()
+            .map(|total| (self.done() as f64 / total as f64).min(1.0))
+    }
+
+    pub fn reset(&self) {
+        self.start(None)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct CacheEntry {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// Unix seconds of the last time GameBanana confirmed this body
+    fetched: u64,
+    body: String,
+}
+
+impl CacheEntry {
+    fn is_fresh(&self) -> bool {
+        now().saturating_sub(self.fetched) < CACHE_TTL.as_secs()
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// FNV-1a, std's hasher isn't guaranteed to be stable between releases
+fn key(url: &str) -> String {
+    let hash = url.bytes().fold(0xcbf29ce484222325u64, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}.json")
+}
+
+fn entry_path(url: &str) -> Result<PathBuf> {
+    Ok(http_cache_path()?.join(key(url)))
+}
+
+fn load(url: &str) -> Option<CacheEntry> {
+    let file = fs::File::open(entry_path(url).ok()?).ok()?;
+    let entry: CacheEntry = serde_json::from_reader(file).ok()?;
+    (entry.url == url).then_some(entry)
+}
+
+fn store(entry: &CacheEntry) -> Result<()> {
+    let file = fs::File::create(entry_path(&entry.url)?)?;
+    serde_json::to_writer(file, entry)?;
+    Ok(())
+}
+
+/// GET a GameBanana API url through the on-disk cache. Fresh entries are used as is,
+/// stale ones are revalidated with their ETag/Last-Modified and served when the
+/// network is unavailable
+pub async fn get_text(url: &str) -> Result<String> {
+    let cached = load(url);
+    if let Some(entry) = cached.as_ref().filter(|e| e.is_fresh() || is_offline()) {
+        trace!(url:%; "Serving cached response");
+        return Ok(entry.body.clone());
+    }
+    ensure_online(url)?;
+    match revalidate(url, cached.as_ref()).await {
+        Ok(Some(entry)) => {
+            if let Err(e) = store(&entry) {
+                warn!(url:%; "Couldn't cache response: {e:#}");
+            }
+            Ok(entry.body)
+        }
+        Ok(None) => {
+            let mut entry = cached.expect("Only revalidated when cached");
+            debug!(url:%; "Cached response still valid");
+            entry.fetched = now();
+            let _ = store(&entry);
+            Ok(entry.body)
+        }
+        Err(e) => match cached {
+            Some(entry) => {
+                warn!(url:%; "Request failed, serving stale response: {e:#}");
+                Ok(entry.body)
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// None when the server says our cached copy is still good
+async fn revalidate(url: &str, cached: Option<&CacheEntry>) -> Result<Option<CacheEntry>> {
+    let mut req = client().get(url).timeout(config().timeout);
+    if let Some(entry) = cached {
+        if let Some(etag) = &entry.etag {
+            req = req.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(modified) = &entry.last_modified {
+            req = req.header(IF_MODIFIED_SINCE, modified);
+        }
+    }
+    info!(url:%; "Requesting");
+    let (resp, _permit) = execute(req).await?;
+    if resp.status() == StatusCode::NOT_MODIFIED && cached.is_some() {
+        return Ok(None);
+    }
+    let resp = resp.error_for_status()?;
+    let header = |name| {
+        resp.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+    };
+    let (etag, last_modified) = (header(ETAG), header(LAST_MODIFIED));
+    Ok(Some(CacheEntry {
+        url: url.to_string(),
+        etag,
+        last_modified,
+        fetched: now(),
+        body: resp.text().await?,
+    }))
+}
+
+/// Drops cached responses not confirmed for longer than `age`, returns bytes freed
+pub fn purge(age: Duration) -> Result<u64> {
+    let mut freed = 0;
+    let cutoff = SystemTime::now()
+        .checked_sub(age)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    for entry in fs::read_dir(http_cache_path()?)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        if meta.is_file() && meta.modified()? < cutoff {
+            fs::remove_file(entry.path())?;
+            freed += meta.len();
+        }
+    }
+    Ok(freed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles() {
+        let base = config().backoff;
+        assert_eq!(backoff(0, None), base);
+        assert_eq!(backoff(3, None), base * 8);
+        let hours = Some(Duration::from_secs(6 * 3600));
+        assert_eq!(backoff(0, hours), MAX_RETRY_AFTER);
+        assert!(is_transient(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_transient(StatusCode::BAD_GATEWAY));
+        assert!(!is_transient(StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn user_agents_validate() {
+        assert!(user_agent_arg("ggmod/3.1 (me@example.com)").is_ok());
+        assert!(user_agent_arg("ggmod\nX-Evil: 1").is_err());
+    }
+
+    #[test]
+    fn cache_keys_stable() {
+        assert_eq!(key(""), "cbf29ce484222325.json");
+        assert_ne!(key("https://a/?x=1"), key("https://a/?x=2"));
+    }
+
+    #[test]
+    fn progress_is_shared() {
+        let progress = Progress::default();
+        let shown = progress.clone();
+        progress.advance(10);
+        assert_eq!((shown.done(), shown.ratio()), (10, None));
+        progress.start(Some(40));
+        progress.advance(10);
+        assert_eq!(shown.ratio(), Some(0.25));
+        shown.reset();
+        assert_eq!((progress.done(), progress.total()), (0, None));
+    }
+}
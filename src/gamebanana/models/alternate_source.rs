@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+/// Mirror the submitter listed next to the GameBanana files, usually a Drive or Mega
+/// link so it isn't always a direct download
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct GBAlternateSource {
+    pub url: String,
+    pub description: String,
+}
@@ -2,36 +2,67 @@ use log::info;
 use ratatui::widgets::Row;
 use serde::{Deserialize, Serialize};
 
-use crate::gamebanana::to_human;
+use crate::gamebanana::api;
 use anyhow::Result;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GBModCategory {
+    /// apiv11 leaves this out for the root category of search records
+    #[serde(default)]
     pub row: usize,
+    #[serde(default)]
     pub icon_url: String,
     pub name: String,
-    //pub item_count: usize,
+    #[serde(default)]
+    pub item_count: usize,
+    #[serde(default)]
+    pub category_count: usize,
 }
 
 impl GBModCategory {
+    /// Direct children of `id` with a "None" entry up front for not filtering at all
     pub async fn build(id: usize) -> Result<Vec<GBModCategory>> {
-        let resp = reqwest::get(Self::url(id)).await?.text().await?;
-        let conv = to_human(&resp)?;
-        info!("successful mod page conversion");
-        let mut cats = serde_json::from_str::<Vec<GBModCategory>>(&conv)?;
+        let mut cats = Self::children(id).await?;
         cats.insert(
             0,
             GBModCategory {
                 row: 0,
                 icon_url: String::from(""),
                 name: String::from("None"),
+                item_count: 0,
+                category_count: 0,
             },
         );
         Ok(cats)
     }
 
+    pub async fn children(id: usize) -> Result<Vec<GBModCategory>> {
+        let cats = api::get_list(&Self::url(id)).await?;
+        info!(category_id = id; "successful category conversion");
+        Ok(cats.records)
+    }
+
+    /// Every category below `id` paired with its depth, parents come before children
+    pub async fn tree(id: usize) -> Result<Vec<(usize, GBModCategory)>> {
+        let mut tree = Vec::new();
+        let mut stack: Vec<(usize, GBModCategory)> = Self::children(id)
+            .await?
+            .into_iter()
+            .rev()
+            .map(|c| (0, c))
+            .collect();
+        while let Some((depth, cat)) = stack.pop() {
+            if cat.category_count > 0 {
+                let children = Self::children(cat.row).await?;
+                stack.extend(children.into_iter().rev().map(|c| (depth + 1, c)));
+            }
+            tree.push((depth, cat));
+        }
+        Ok(tree)
+    }
+
     fn url(id: usize) -> String {
-        format!("https://gamebanana.com/apiv11/Mod/Categories?_idCategoryRow={id}&_sSort=a_to_z")
+        api::url(&format!("Mod/Categories?_idCategoryRow={id}&_sSort=a_to_z"))
     }
 }
 
@@ -1,16 +1,27 @@
-use std::{fs, io, path};
+use std::{
+    fs, io, path, process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use compress_tools::{uncompress_archive, Ownership};
 use log::{debug, info, trace, warn};
 use ratatui::widgets::Row;
 use serde::{Deserialize, Serialize};
 use unrar::Archive;
 
-use crate::download_path;
+use crate::{
+    gamebanana::http::{self, Progress},
+    mod_cache_path, SCRATCH_PREFIX,
+};
+
+/// Tells apart attempts at the same file, a cancelled one may still be extracting
+static ATTEMPT: AtomicUsize = AtomicUsize::new(0);
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GBFile {
+    #[serde(default)]
+    pub row: usize,
     pub contains_exe: bool,
     pub download_count: usize,
     pub filesize: usize,
@@ -22,45 +33,116 @@ pub struct GBFile {
 }
 
 impl GBFile {
-    async fn download_to<'a>(&self, path: &'a path::PathBuf) -> Result<&'a path::PathBuf> {
-        info!("Downloading new archive..");
-        let response = reqwest::get(&self.download_url).await?;
+    async fn download_to<'a>(
+        &self,
+        url: &str,
+        path: &'a path::PathBuf,
+        progress: &Progress,
+    ) -> Result<&'a path::PathBuf> {
+        info!(url:%, path:?; "Downloading new archive..");
+        let bytes = http::get_bytes_tracked(url, progress).await?;
+        // Mirrors tend to be share pages rather than files
+        if bytes.trim_ascii_start().starts_with(b"<") {
+            bail!("{url} is a web page, not an archive");
+        }
         let mut file = fs::File::create(path)?;
-        let mut content = io::Cursor::new(response.bytes().await?);
+        let mut content = io::Cursor::new(bytes);
         io::copy(&mut content, &mut file)?;
         Ok(path)
     }
 
-    pub async fn fetch(&self) -> Result<path::PathBuf> {
-        let file = download_path().unwrap_or_default().join(&self.file);
+    pub async fn fetch(&self, mod_id: usize, progress: &Progress) -> Result<path::PathBuf> {
+        self.fetch_from(mod_id, &self.download_url, progress).await
+    }
+
+    /// Same as `fetch` but downloads the archive from `url`, e.g. an alternate source.
+    /// Everything happens in a scratch dir and is only renamed into place once fully
+    /// extracted, so the mod dir existing means it's complete
+    pub async fn fetch_from(
+        &self,
+        mod_id: usize,
+        url: &str,
+        progress: &Progress,
+    ) -> Result<path::PathBuf> {
+        let file = mod_cache_path(mod_id, self.row)?.join(&self.file);
         let dir = file.with_extension("");
-        if dir.exists() && dir.is_dir() {
-            trace!("Mod already exists, doing nothing");
-        } else {
-            self.download_to(&file).await?;
-            debug!("Archive {file:?} attempting decompress to {dir:?}");
-            if let Some(ext) = file.extension() {
-                if ext == "rar" {
-                    let mut archive = Archive::new(&file).open_for_processing().unwrap();
-                    while let Some(header) = archive.read_header()? {
-                        archive = if header.entry().is_file() {
-                            header.extract_with_base(&dir)?
-                        } else {
-                            header.skip()?
-                        };
-                    }
-                } else {
-                    let src = fs::File::open(&file)?;
-                    uncompress_archive(src, &dir, Ownership::Preserve)?;
+        if dir.is_dir() {
+            trace!(mod_id, path:? = dir; "Mod already exists, doing nothing");
+            return Ok(dir);
+        }
+        let scratch = Scratch::new(&file)?;
+        let archive = scratch.0.join(&self.file);
+        self.download_to(url, &archive, progress).await?;
+        debug!(mod_id, archive:? = archive, path:? = dir; "Archive attempting decompress");
+        let out = dir.clone();
+        // Big archives take a while, keep it off the runtime's threads. The scratch
+        // dir goes along, an aborted task doesn't stop this and it cleans up after
+        tokio::task::spawn_blocking(move || -> Result<()> {
+            let extracted = archive.with_extension("");
+            extract(mod_id, &archive, &extracted)?;
+            // Lost the race to another download of the same file, theirs is as good
+            if let Err(e) = fs::rename(&extracted, &out) {
+                if !out.is_dir() {
+                    return Err(e.into());
                 }
-            } else {
-                warn!("Extentionless archive, wtf: {file:?}");
             }
-        }
+            // The archive is kept next to the mod like it always was
+            let _ = fs::rename(&archive, &file);
+            drop(scratch);
+            Ok(())
+        })
+        .await??;
         Ok(dir)
     }
 }
 
+/// Working dir of one download attempt, removed with whatever is left in it
+struct Scratch(path::PathBuf);
+
+impl Scratch {
+    fn new(file: &path::Path) -> Result<Scratch> {
+        let n = ATTEMPT.fetch_add(1, Ordering::Relaxed);
+        let path = file.with_file_name(format!("{SCRATCH_PREFIX}{}-{n}", process::id()));
+        fs::create_dir_all(&path)?;
+        Ok(Scratch(path))
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.0) {
+            warn!(path:? = self.0; "Couldn't remove download leftovers: {e}");
+        }
+    }
+}
+
+fn extract(mod_id: usize, file: &path::Path, dir: &path::Path) -> Result<()> {
+    match file.extension() {
+        Some(ext) if ext == "rar" => {
+            let mut archive = Archive::new(file).open_for_processing()?;
+            while let Some(header) = archive.read_header()? {
+                archive = if header.entry().is_file() {
+                    header.extract_with_base(dir)?
+                } else {
+                    header.skip()?
+                };
+            }
+        }
+        Some(_) => {
+            let src = fs::File::open(file)?;
+            uncompress_archive(src, dir, Ownership::Preserve)?;
+        }
+        None => {
+            warn!(mod_id, path:? = file; "Extentionless archive, wtf");
+            bail!(
+                "{} has no extension, can't tell how to extract it",
+                file.display()
+            );
+        }
+    }
+    Ok(())
+}
+
 impl From<GBFile> for Row<'_> {
     fn from(value: GBFile) -> Self {
         Row::new(vec![
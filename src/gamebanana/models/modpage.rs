@@ -1,13 +1,25 @@
 use std::path;
 
-use log::info;
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
 
-use crate::gamebanana::to_human;
+use crate::gamebanana::{api, http::Progress, lenient};
 
-use super::{category::GBModCategory, file::GBFile};
+use super::{
+    alternate_source::GBAlternateSource,
+    category::GBModCategory,
+    file::GBFile,
+    game::GBGame,
+    preview::{images, GBPreviewMedia},
+    submitter::GBSubmitter,
+    tag::GBTag,
+    update::GBUpdate,
+};
 use anyhow::Result;
 
+/// How many of the most recent updates are fetched with a page
+const LATEST_UPDATES: usize = 5;
+
 fn default_nsfw() -> bool {
     false
 }
@@ -16,34 +28,62 @@ fn default_nsfw() -> bool {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GBModPage {
     pub category: GBModCategory,
+    #[serde(default)]
     pub files: Vec<GBFile>,
     pub name: String,
+    #[serde(default)]
     pub description: String,
     pub row: usize,
     #[serde(default = "default_nsfw")]
     pub is_nsfw: bool,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default, deserialize_with = "lenient")]
+    pub submitter: Option<GBSubmitter>,
+    #[serde(default)]
+    pub date_added: usize,
+    #[serde(default)]
+    pub date_updated: usize,
+    #[serde(default)]
+    pub has_updates: bool,
+    #[serde(default, deserialize_with = "lenient")]
+    pub latest_updates: Vec<GBUpdate>,
+    #[serde(default)]
+    pub profile_url: String,
+    /// Parent of `category`, e.g. Skins for a character
+    #[serde(default, deserialize_with = "lenient")]
+    pub super_category: Option<GBModCategory>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub game: Option<GBGame>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub alternate_file_sources: Vec<GBAlternateSource>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub tags: Vec<GBTag>,
+    #[serde(default, deserialize_with = "images")]
+    pub preview_media: Vec<GBPreviewMedia>,
 }
 
-const PROPS: [&str; 14] = [
-    "_sName",
-    "_aGame",
-    "_sProfileUrl",
-    "_aPreviewMedia",
-    "_sDescription",
-    "_aSubmitter",
-    "_aCategory",
-    "_aSuperCategory",
-    "_aFiles",
-    "_tsDateUpdated",
-    "_aAlternateFileSources",
-    "_bHasUpdates",
-    "_aLatestUpdates",
-    "_idRow",
-];
-
 impl GBModPage {
-    pub async fn download_file(&self, idx: usize) -> Result<path::PathBuf> {
-        self.files[idx].fetch().await
+    /// Falls back to the alternate sources when GameBanana's own download fails
+    pub async fn download_file(&self, idx: usize, progress: &Progress) -> Result<path::PathBuf> {
+        let file = &self.files[idx];
+        let mut res = file.fetch(self.row, progress).await;
+        for source in &self.alternate_file_sources {
+            let Err(e) = &res else { break };
+            warn!(mod_id = self.row, url:% = source.url; "Download failed, trying alternate source: {e:#}");
+            res = file.fetch_from(self.row, &source.url, progress).await;
+        }
+        res
+    }
+
+    /// Caches the first preview image, the one local mods show. Failing is only logged
+    pub async fn download_preview(&self) -> Option<path::PathBuf> {
+        let media = self.preview_media.first()?;
+        media
+            .fetch(self.row)
+            .await
+            .inspect_err(|e| warn!(mod_id = self.row; "Couldn't cache preview: {e:#}"))
+            .ok()
     }
 
     pub fn set_nsfw(mut self) -> Self {
@@ -52,14 +92,54 @@ impl GBModPage {
     }
 
     pub async fn build(id: usize) -> Result<GBModPage> {
-        let resp = reqwest::get(Self::url(id)).await?.text().await?;
-        let conv = to_human(&resp)?;
-        info!("successful mod page conversion");
-        Ok(serde_json::from_str::<GBModPage>(&conv)?)
+        let page_url = api::url(&format!("Mod/{id}/ProfilePage"));
+        let updates_url = api::url(&format!(
+            "Mod/{id}/Updates?_nPage=1&_nPerpage={LATEST_UPDATES}"
+        ));
+        let (page, updates) = tokio::join!(
+            api::get::<GBModPage>(&page_url),
+            api::get_list::<GBUpdate>(&updates_url)
+        );
+        let mut page = page?;
+        info!(mod_id = id; "successful mod page conversion");
+        // Updates are a nice to have, not worth failing the whole page over
+        match updates {
+            Ok(updates) if page.latest_updates.is_empty() => {
+                page.has_updates |= !updates.records.is_empty();
+                page.latest_updates = updates.records;
+            }
+            Ok(_) => (),
+            Err(e) => warn!(mod_id = id; "Couldn't get updates: {e:#}"),
+        }
+        Ok(page)
     }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gamebanana::to_human;
 
-    fn url(id: usize) -> String {
-        // INFO: Could switch to api.gamebanana.com in future
-        format!("https://gamebanana.com/apiv6/Mod/{id}?_csvProperties=") + &PROPS.join(",")
+    #[test]
+    fn profile_pages_parse() {
+        let page = r#"{
+            "_idRow": 9, "_sName": "Red Sol", "_sProfileUrl": "https://gamebanana.com/mods/9",
+            "_aCategory": {"_idRow": 3, "_sName": "Sol Badguy", "_sIconUrl": ""},
+            "_aSuperCategory": {"_idRow": 2, "_sName": "Skins"},
+            "_aSubmitter": {"_idRow": 77, "_sName": "Kyfan"},
+            "_aAlternateFileSources": [{"url": "https://mega.nz/x", "description": "mirror"}],
+            "_aTags": ["Red", {"_sTitle": "Character", "_sValue": "Sol"}],
+            "_aPreviewMedia": {"_aImages": [{"_sType": "screenshot", "_sBaseUrl": "https://x",
+                "_sFile": "a.jpg"}]},
+            "_aFiles": []
+        }"#;
+        let page: GBModPage = serde_json::from_str(&to_human(page).unwrap()).unwrap();
+        assert_eq!(page.super_category.unwrap().name, "Skins");
+        assert_eq!(page.submitter.unwrap().row, 77);
+        assert_eq!(page.alternate_file_sources[0].description, "mirror");
+        assert_eq!(page.profile_url, "https://gamebanana.com/mods/9");
+        assert_eq!(page.preview_media[0].file, "a.jpg");
+        let tags: Vec<String> = page.tags.iter().map(ToString::to_string).collect();
+        assert_eq!(tags, vec!["Red", "Character: Sol"]);
     }
 }
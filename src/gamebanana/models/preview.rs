@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use log::{debug, info, trace};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use std::{
     fs,
@@ -10,7 +10,7 @@ use std::{
 
 use anyhow::Result;
 
-use crate::download_path;
+use crate::{gamebanana::http, media_cache_path};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GBPreviewMedia {
@@ -18,26 +18,61 @@ pub struct GBPreviewMedia {
     pub media_type: String,
     pub base_url: String,
     pub file: String,
+    /// 100px wide version of `file`, not every image has one
+    #[serde(default, rename = "file100")]
+    pub thumb_file: Option<String>,
+}
+
+/// apiv11 nests the list as `{"_aImages": [..]}`, the bare list is still accepted
+pub fn images<'de, D>(deserializer: D) -> Result<Vec<GBPreviewMedia>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Media {
+        Nested {
+            #[serde(default)]
+            images: Vec<GBPreviewMedia>,
+        },
+        Bare(Vec<GBPreviewMedia>),
+    }
+    Ok(match Media::deserialize(deserializer)? {
+        Media::Nested { images } => images,
+        Media::Bare(images) => images,
+    })
 }
 
 impl GBPreviewMedia {
-    pub async fn fetch(&self) -> Result<PathBuf> {
-        let file = download_path().unwrap_or_default().join(&self.file);
+    pub async fn fetch(&self, mod_id: usize) -> Result<PathBuf> {
+        let file = media_cache_path(mod_id)?.join(&self.file);
         if file.exists() && file.is_file() {
-            trace!("Preview media already exists, doing nothing");
+            trace!(mod_id, path:? = file; "Preview media already exists, doing nothing");
         } else {
-            debug!("{}", format!("Archive {file:?} attempting to download"));
-            self.download_to(&file).await?;
+            debug!(mod_id, path:? = file; "Preview media attempting to download");
+            self.download_to(&self.file, &file).await?;
+        }
+        Ok(file)
+    }
+
+    /// Small version for thumbnails, the full image if there's none
+    pub async fn fetch_thumb(&self, mod_id: usize) -> Result<PathBuf> {
+        let Some(thumb) = &self.thumb_file else {
+            return self.fetch(mod_id).await;
+        };
+        let file = media_cache_path(mod_id)?.join(thumb);
+        if !file.is_file() {
+            self.download_to(thumb, &file).await?;
         }
         Ok(file)
     }
 
-    async fn download_to<'a>(&self, path: &'a PathBuf) -> Result<&'a PathBuf> {
-        info!("Downloading new archive..");
-        let url = format!("{}/{}", self.base_url.clone(), &self.file);
-        let response = reqwest::get(url).await?;
+    async fn download_to<'a>(&self, name: &str, path: &'a PathBuf) -> Result<&'a PathBuf> {
+        let url = format!("{}/{name}", self.base_url);
+        info!(url:%, path:?; "Downloading preview media..");
+        let bytes = http::get_bytes(&url).await?;
         let mut file = fs::File::create(path)?;
-        let mut content = io::Cursor::new(response.bytes().await?);
+        let mut content = io::Cursor::new(bytes);
         io::copy(&mut content, &mut file)?;
         Ok(path)
     }
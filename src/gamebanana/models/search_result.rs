@@ -1,15 +1,23 @@
-use std::path::PathBuf;
-
 use ratatui::{
     style::{Color, Stylize},
     widgets::Row,
 };
 use serde::{Deserialize, Serialize};
 
-use super::{category::GBModCategory, game::GBGame, modpage::GBModPage, preview::GBPreviewMedia};
+use super::{
+    alternate_source::GBAlternateSource,
+    category::GBModCategory,
+    game::GBGame,
+    modpage::GBModPage,
+    preview::{images, GBPreviewMedia},
+    submitter::GBSubmitter,
+};
+use crate::gamebanana::lenient;
 use anyhow::Result;
 
+/// Record of a `{Model}/Index` listing, counts and texts apiv11 leaves out are zero/empty
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "RawSearchEntry")]
 pub struct GBSearchEntry {
     pub date_updated: usize,
     pub date_added: usize,
@@ -25,6 +33,73 @@ pub struct GBSearchEntry {
     pub description: String,
     pub category: GBModCategory,
     pub game: GBGame,
+    pub submitter: Option<GBSubmitter>,
+    pub profile_url: String,
+    pub alternate_file_sources: Vec<GBAlternateSource>,
+}
+
+/// What is actually sent, apiv11 can have both the old and new name of a field in one
+/// record which serde aliases refuse as duplicates
+#[derive(Deserialize)]
+struct RawSearchEntry {
+    #[serde(default)]
+    date_updated: usize,
+    date_added: usize,
+    model_name: String,
+    name: String,
+    is_nsfw: Option<bool>,
+    has_content_ratings: Option<bool>,
+    row: usize,
+    #[serde(default, deserialize_with = "images")]
+    preview_media: Vec<GBPreviewMedia>,
+    #[serde(default)]
+    download_count: usize,
+    #[serde(default)]
+    view_count: usize,
+    #[serde(default)]
+    like_count: usize,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    description: String,
+    category: Option<GBModCategory>,
+    root_category: Option<GBModCategory>,
+    game: GBGame,
+    #[serde(default, deserialize_with = "lenient")]
+    submitter: Option<GBSubmitter>,
+    #[serde(default)]
+    profile_url: String,
+    #[serde(default, deserialize_with = "lenient")]
+    alternate_file_sources: Vec<GBAlternateSource>,
+}
+
+impl TryFrom<RawSearchEntry> for GBSearchEntry {
+    type Error = &'static str;
+
+    fn try_from(raw: RawSearchEntry) -> Result<Self, Self::Error> {
+        Ok(GBSearchEntry {
+            date_updated: raw.date_updated,
+            date_added: raw.date_added,
+            model_name: raw.model_name,
+            name: raw.name,
+            is_nsfw: raw.is_nsfw.unwrap_or_default() || raw.has_content_ratings.unwrap_or_default(),
+            row: raw.row,
+            preview_media: raw.preview_media,
+            download_count: raw.download_count,
+            view_count: raw.view_count,
+            like_count: raw.like_count,
+            text: raw.text,
+            description: raw.description,
+            category: raw
+                .category
+                .or(raw.root_category)
+                .ok_or("missing field `category`")?,
+            game: raw.game,
+            submitter: raw.submitter,
+            profile_url: raw.profile_url,
+            alternate_file_sources: raw.alternate_file_sources,
+        })
+    }
 }
 
 impl GBSearchEntry {
@@ -33,18 +108,6 @@ impl GBSearchEntry {
             .await
             .map(|page| if self.is_nsfw { page.set_nsfw() } else { page })
     }
-
-    pub async fn download_media(&self, count: usize) -> Vec<PathBuf> {
-        let mut collected_media = Vec::new();
-        for (i, media) in self.preview_media.clone().iter().enumerate() {
-            if i == count {
-                break;
-            }
-            let m = media.fetch().await;
-            collected_media.push(m);
-        }
-        collected_media.into_iter().filter_map(Result::ok).collect()
-    }
 }
 
 impl From<GBSearchEntry> for Row<'_> {
@@ -52,6 +115,7 @@ impl From<GBSearchEntry> for Row<'_> {
         let row = Row::new(vec![
             value.name,
             value.category.name,
+            value.submitter.map(|s| s.name).unwrap_or_default(),
             value.view_count.to_string(),
             value.like_count.to_string(),
             value.download_count.to_string(),
@@ -64,3 +128,39 @@ impl From<GBSearchEntry> for Row<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gamebanana::to_human;
+
+    #[test]
+    fn index_records_parse() {
+        let record = r#"{
+            "_idRow": 501, "_sModelName": "Mod", "_sName": "Red Sol", "_tsDateAdded": 1700000000,
+            "_aPreviewMedia": {"_aImages": [{"_sType": "screenshot",
+                "_sBaseUrl": "https://images.gamebanana.com/img/ss/mods", "_sFile": "a.jpg",
+                "_sFile100": "a-100.jpg"}]},
+            "_aGame": {"_idRow": 11534, "_sName": "Guilty Gear -Strive-"},
+            "_aRootCategory": {"_sName": "Skins", "_sIconUrl": "https://x/y.png"},
+            "_bHasContentRatings": true, "_nLikeCount": 3, "_nViewCount": 40
+        }"#;
+        let entry: GBSearchEntry = serde_json::from_str(&to_human(record).unwrap()).unwrap();
+        assert_eq!(entry.preview_media[0].file, "a.jpg");
+        assert_eq!(
+            entry.preview_media[0].thumb_file.as_deref(),
+            Some("a-100.jpg")
+        );
+        assert_eq!(entry.category.name, "Skins");
+        assert!(entry.is_nsfw);
+        assert_eq!(entry.download_count, 0);
+        let both = record.replace(
+            r#""_bHasContentRatings": true"#,
+            r#""_bHasContentRatings": false, "_bIsNsfw": true,
+            "_aCategory": {"_sName": "Ky", "_sIconUrl": ""}"#,
+        );
+        let entry: GBSearchEntry = serde_json::from_str(&to_human(&both).unwrap()).unwrap();
+        assert!(entry.is_nsfw);
+        assert_eq!(entry.category.name, "Ky");
+    }
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GBSubmitter {
+    pub row: usize,
+    pub name: String,
+    #[serde(default)]
+    pub profile_url: String,
+    #[serde(default)]
+    pub avatar_url: String,
+}
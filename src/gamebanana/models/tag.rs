@@ -0,0 +1,21 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Tags on a profile page, either plain labels or title/value pairs like
+/// `Character: Ky Kiske`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum GBTag {
+    Plain(String),
+    Pair { title: String, value: String },
+}
+
+impl fmt::Display for GBTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GBTag::Plain(tag) => write!(f, "{tag}"),
+            GBTag::Pair { title, value } => write!(f, "{title}: {value}"),
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// Record of `Mod/{id}/Updates`, what the submitter posted alongside a new version
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct GBUpdate {
+    pub name: String,
+    pub version: String,
+    pub date_added: usize,
+    pub change_log: Vec<GBChangeLogEntry>,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct GBChangeLogEntry {
+    pub text: String,
+    pub cat: String,
+}
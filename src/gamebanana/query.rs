@@ -0,0 +1,101 @@
+use serde::Serialize;
+
+use super::models::search_result::GBSearchEntry;
+
+/// Everything a search can be narrowed down by, all set fields have to match. Name and
+/// category are sent to GameBanana, the rest isn't filterable there and is checked on
+/// each page as it comes in, so filtered pages can come out short
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct SearchQuery {
+    pub name: Option<String>,
+    pub category: Option<usize>,
+    /// Submitter id or a case-insensitive part of their name
+    pub submitter: Option<String>,
+    /// Unix timestamp, added or updated at or after this
+    pub updated_since: Option<usize>,
+    pub min_downloads: Option<usize>,
+}
+
+impl SearchQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Empty names and the "None" category (0) count as not set
+    pub fn name(mut self, name: Option<&str>) -> Self {
+        self.name = name.filter(|n| !n.is_empty()).map(String::from);
+        self
+    }
+
+    pub fn category(mut self, cat_id: Option<usize>) -> Self {
+        self.category = cat_id.filter(|id| *id != 0);
+        self
+    }
+
+    pub fn submitter(mut self, submitter: Option<&str>) -> Self {
+        self.submitter = submitter.filter(|s| !s.is_empty()).map(String::from);
+        self
+    }
+
+    pub fn updated_since(mut self, ts: Option<usize>) -> Self {
+        self.updated_since = ts;
+        self
+    }
+
+    pub fn min_downloads(mut self, count: Option<usize>) -> Self {
+        self.min_downloads = count;
+        self
+    }
+
+    /// Whether anything has to be checked after GameBanana answered
+    pub fn filters_locally(&self) -> bool {
+        self.submitter.is_some() || self.updated_since.is_some() || self.min_downloads.is_some()
+    }
+
+    /// Only the parts GameBanana can't filter by
+    pub fn matches(&self, entry: &GBSearchEntry) -> bool {
+        let submitter = self.submitter.as_ref().is_none_or(|wanted| {
+            entry.submitter.as_ref().is_some_and(|s| {
+                wanted.parse() == Ok(s.row)
+                    || s.name.to_lowercase().contains(&wanted.to_lowercase())
+            })
+        });
+        let updated = self
+            .updated_since
+            .is_none_or(|ts| entry.date_updated.max(entry.date_added) >= ts);
+        let downloads = self
+            .min_downloads
+            .is_none_or(|min| entry.download_count >= min);
+        submitter && updated && downloads
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gamebanana::to_human;
+
+    #[test]
+    fn queries_combine() {
+        let entry: GBSearchEntry = serde_json::from_str(
+            &to_human(
+                r#"{"_idRow": 1, "_sModelName": "Mod", "_sName": "Blue Ky",
+                "_tsDateAdded": 1000, "_tsDateUpdated": 5000, "_nDownloadCount": 300,
+                "_aSubmitter": {"_idRow": 77, "_sName": "Kyfan"},
+                "_aGame": {"_idRow": 11534, "_sName": "GGST"},
+                "_aRootCategory": {"_sName": "Skins"}}"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let query = SearchQuery::new().name(Some("ky")).category(Some(0));
+        assert_eq!(query.category, None);
+        assert!(!query.filters_locally() && query.matches(&entry));
+        let query = query.submitter(Some("KYF")).updated_since(Some(4000));
+        assert!(query.matches(&entry));
+        assert!(query.clone().submitter(Some("77")).matches(&entry));
+        assert!(!query.clone().submitter(Some("sol")).matches(&entry));
+        assert!(!query.clone().min_downloads(Some(301)).matches(&entry));
+        assert!(!query.updated_since(Some(6000)).matches(&entry));
+    }
+}
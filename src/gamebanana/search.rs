@@ -1,34 +1,141 @@
-use log::{info, trace};
+use std::{fmt, time::Duration};
 
-use crate::gamebanana::to_human;
+use futures::{stream, Stream};
+use log::info;
+use reqwest::Url;
+use serde::Serialize;
 
-use super::models::search_result::GBSearchEntry;
+use super::{api, models::search_result::GBSearchEntry, query::SearchQuery};
 use anyhow::Result;
 
+/// Pause between consecutive page requests when walking through every page
+pub const PAGE_DELAY: Duration = Duration::from_millis(500);
+
 pub struct Search {
-    url: String,
+    url: Url,
+    per_page: usize,
+    filter: SearchQuery,
+}
+
+/// Where a page sits among all results, total is None when GameBanana didn't say
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct PageInfo {
+    pub page: usize,
+    pub per_page: usize,
+    pub total: Option<usize>,
+}
+
+impl PageInfo {
+    pub fn page_count(&self) -> Option<usize> {
+        self.total.map(|t| t.div_ceil(self.per_page.max(1)).max(1))
+    }
+}
+
+impl fmt::Display for PageInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.page_count() {
+            Some(count) => write!(f, "page {}/{count}", self.page),
+            None => write!(f, "page {}", self.page),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchPage {
+    pub info: PageInfo,
+    pub entries: Vec<GBSearchEntry>,
+    /// How many entries GameBanana sent before client side filtering
+    pub fetched: usize,
+}
+
+impl SearchPage {
+    pub fn is_last(&self) -> bool {
+        match self.info.page_count() {
+            Some(count) => self.info.page >= count,
+            None => self.fetched < self.info.per_page,
+        }
+    }
 }
 
 impl Search {
-    // TODO: Could use some external cache later on
-    pub fn base(s: &str) -> Search {
+    pub fn from_url(url: Url) -> Search {
         Search {
-            url: String::from("https://gamebanana.com/apiv6/") + s,
+            url,
+            per_page: 30,
+            filter: SearchQuery::default(),
         }
     }
 
-    fn page(&self, n: usize) -> String {
-        self.url.clone() + &format!("&_nPage={n}")
+    /// Entries not matching the client side parts of `filter` are dropped from pages
+    pub fn with_filter(mut self, filter: SearchQuery) -> Search {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_page_size(mut self, per_page: usize) -> Search {
+        self.per_page = per_page;
+        self
     }
 
-    pub async fn read_page(&self, n: usize) -> Result<Vec<GBSearchEntry>> {
+    fn page(&self, n: usize) -> Url {
+        let mut url = self.url.clone();
+        url.query_pairs_mut().append_pair("_nPage", &n.to_string());
+        url
+    }
+
+    pub async fn read_page(&self, n: usize) -> Result<SearchPage> {
         let url = self.page(n);
-        info!("url generated: {url}");
-        let resp = reqwest::get(url).await?.text().await?;
-        trace!("resp: {resp}");
-        let conv = to_human(&resp)?;
-        trace!("conv: {conv}");
+        info!(url:%; "url generated");
+        let mut envelope = api::get_list::<GBSearchEntry>(url.as_str()).await?;
         info!("successful search page conversion");
-        Ok(serde_json::from_str::<Vec<GBSearchEntry>>(&conv)?)
+        let info = PageInfo {
+            page: n,
+            per_page: envelope.metadata.perpage.unwrap_or(self.per_page),
+            total: envelope.metadata.record_count,
+        };
+        let fetched = envelope.records.len();
+        if self.filter.filters_locally() {
+            envelope.records.retain(|e| self.filter.matches(e));
+        }
+        Ok(SearchPage {
+            info,
+            entries: envelope.records,
+            fetched,
+        })
+    }
+
+    /// Every page from `start` until the last one, waiting `delay` between requests.
+    /// Stops after the first error
+    pub fn pages(self, start: usize, delay: Duration) -> impl Stream<Item = Result<SearchPage>> {
+        stream::unfold(Some((self, start)), move |state| async move {
+            let (search, n) = state?;
+            if n > start {
+                tokio::time::sleep(delay).await;
+            }
+            let page = search.read_page(n).await;
+            let next = match &page {
+                Ok(p) if !p.is_last() => Some((search, n + 1)),
+                _ => None,
+            };
+            Some((page, next))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn page_info_counts() {
+        let info = |page, total| PageInfo {
+            page,
+            per_page: 15,
+            total,
+        };
+        assert_eq!(info(3, Some(180)).to_string(), "page 3/12");
+        assert_eq!(info(1, Some(181)).page_count(), Some(13));
+        assert_eq!(info(1, Some(0)).to_string(), "page 1/1");
+        assert_eq!(info(2, None).to_string(), "page 2");
     }
 }
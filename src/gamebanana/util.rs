@@ -1,4 +1,14 @@
+use std::sync::LazyLock;
+
 use regex::Regex;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+
+static BREAKS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)<br\s*/?>|</p>|</div>|</h[1-6]>|</li>|</?[uo]l[^>]*>").unwrap()
+});
+static BULLETS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)<li[^>]*>").unwrap());
+static TAGS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").unwrap());
+static BLANKS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\n\s*\n\s*\n+").unwrap());
 
 fn to_snake_case(s: &str) -> String {
     let mut new = String::new();
@@ -35,9 +45,129 @@ pub fn to_human(s: &str) -> Result<String, regex::Error> {
     Ok(res)
 }
 
+/// For fields GameBanana isn't consistent about, falls back to default instead of
+/// failing the whole record
+pub fn lenient<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned + Default,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).unwrap_or_default())
+}
+
+/// Descriptions and update texts come as html, this is good enough for a terminal
+pub fn html_to_text(s: &str) -> String {
+    let res = BREAKS.replace_all(s, "\n");
+    let res = BULLETS.replace_all(&res, "• ");
+    let res = decode_entities(&TAGS.replace_all(&res, ""));
+    let lines: Vec<&str> = res.lines().map(str::trim_end).collect();
+    BLANKS
+        .replace_all(&lines.join("\n"), "\n\n")
+        .trim()
+        .to_string()
+}
+
+/// The entities GameBanana actually uses, `&amp;` last so it isn't decoded twice
+pub fn decode_entities(s: &str) -> String {
+    s.replace("&nbsp;", " ")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Unix timestamp to YYYY-MM-DD (UTC), we only ever show days so no need for chrono
+pub fn format_date(ts: usize) -> String {
+    // Howard Hinnant's civil_from_days
+    let z = (ts / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Byte counts in binary units, e.g. `1.5 MiB`
+pub fn human_size(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", units[unit])
+    }
+}
+
+/// YYYY-MM-DD (UTC) back to a unix timestamp at midnight, the inverse of `format_date`
+pub fn parse_date(s: &str) -> Option<usize> {
+    let mut parts = s.trim().splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+        return None;
+    }
+    // Howard Hinnant's days_from_civil
+    let year = year - (month <= 2) as i64;
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    usize::try_from(days * 86400).ok()
+}
+
 #[cfg(test)]
 mod test {
-    use crate::gamebanana::{to_human, util::to_snake_case};
+    use crate::gamebanana::{format_date, html_to_text, parse_date, to_human, util::to_snake_case};
+
+    #[test]
+    fn html_strips() {
+        assert_eq!(
+            html_to_text("<b>Sol</b> &amp; Ky<br>recolor<ul><li>red</li><li>blue</li></ul>"),
+            "Sol & Ky\nrecolor\n• red\n• blue"
+        );
+    }
+
+    #[test]
+    fn dates_format() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(1722410377), "2024-07-31");
+        assert_eq!(format_date(951782400), "2000-02-29");
+        assert_eq!(parse_date("2000-02-29"), Some(951782400));
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(parse_date("yesterday"), None);
+    }
+
+    #[test]
+    fn impossible_dates_rejected() {
+        assert_eq!(parse_date("2024-02-31"), None);
+        assert_eq!(parse_date("2023-02-29"), None);
+        assert_eq!(parse_date("1900-02-29"), None);
+        assert_eq!(parse_date("2024-04-31"), None);
+        assert_eq!(parse_date("2024-02-29"), Some(1709164800));
+    }
 
     #[test]
     fn snake_case_works() {
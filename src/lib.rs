@@ -1,7 +1,10 @@
 use crate::files::*;
 
+pub mod cache;
 pub mod cli;
+pub mod error;
 pub mod files;
 pub mod gamebanana;
+pub mod logging;
 pub mod modz;
 pub mod tui;
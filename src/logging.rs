@@ -0,0 +1,152 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::{
+    kv::{self, Key, Value, VisitSource},
+    LevelFilter, Log, Metadata, Record,
+};
+
+use crate::gamebanana::format_date;
+
+/// Log files over this size are rotated on startup
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+/// How many rotated logs to keep around, ggmod.log.1 being the newest
+const KEEP_LOGS: usize = 3;
+
+/// Where logs end up when they go to a file, for pointing people at it
+static LOG_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+pub enum LogTarget {
+    File(PathBuf),
+    Stderr,
+}
+
+/// One line per record: time, level, target, message and then any key=value fields
+struct Logger {
+    sink: Mutex<Box<dyn Write + Send>>,
+    level: LevelFilter,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let mut line = format!(
+            "[{}] {:<5} {}: {}",
+            timestamp(),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        let _ = record.key_values().visit(&mut Fields(&mut line));
+        if let Ok(mut sink) = self.sink.lock() {
+            let _ = writeln!(sink, "{line}");
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut sink) = self.sink.lock() {
+            let _ = sink.flush();
+        }
+    }
+}
+
+struct Fields<'a>(&'a mut String);
+
+impl<'kvs> VisitSource<'kvs> for Fields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        let value = value.to_string();
+        if value.is_empty() || value.contains(char::is_whitespace) {
+            let _ = write!(self.0, " {key}={value:?}");
+        } else {
+            let _ = write!(self.0, " {key}={value}");
+        }
+        Ok(())
+    }
+}
+
+fn timestamp() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let secs = now.as_secs() as usize;
+    format!(
+        "{} {:02}:{:02}:{:02}.{:03}",
+        format_date(secs),
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60,
+        now.subsec_millis()
+    )
+}
+
+/// Moves ggmod.log to ggmod.log.1 and so on once it grows too big
+fn rotate(path: &Path) -> io::Result<()> {
+    if fs::metadata(path).map_or(true, |m| m.len() < MAX_LOG_SIZE) {
+        return Ok(());
+    }
+    let numbered = |n: usize| PathBuf::from(format!("{}.{n}", path.display()));
+    for n in (1..KEEP_LOGS).rev() {
+        if numbered(n).exists() {
+            fs::rename(numbered(n), numbered(n + 1))?;
+        }
+    }
+    fs::rename(path, numbered(1))
+}
+
+pub fn init(target: LogTarget, level: LevelFilter) -> io::Result<()> {
+    let sink: Box<dyn Write + Send> = match target {
+        LogTarget::Stderr => Box::new(io::stderr()),
+        LogTarget::File(path) => {
+            rotate(&path)?;
+            let file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)?;
+            let _ = LOG_FILE.set(path);
+            Box::new(file)
+        }
+    };
+    let logger = Logger {
+        sink: Mutex::new(sink),
+        level,
+    };
+    log::set_boxed_logger(Box::new(logger)).map_err(io::Error::other)?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+pub fn log_file() -> Option<&'static Path> {
+    LOG_FILE.get().map(PathBuf::as_path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn logs_rotate() {
+        let dir = env::temp_dir().join("ggmod-log-test");
+        fs::DirBuilder::new().recursive(true).create(&dir).unwrap();
+        let log = dir.join("ggmod.log");
+        fs::write(&log, vec![b'x'; MAX_LOG_SIZE as usize]).unwrap();
+        fs::write(dir.join("ggmod.log.1"), b"older").unwrap();
+        rotate(&log).unwrap();
+        assert!(!log.exists());
+        assert_eq!(fs::read(dir.join("ggmod.log.2")).unwrap(), b"older");
+        rotate(&log).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,7 +1,14 @@
+use std::{path::PathBuf, process};
+
+use anyhow::Result;
 use clap::{Parser, Subcommand};
 use ggmod::cli::*;
+use ggmod::error::report;
+use ggmod::files::log_path;
+use ggmod::gamebanana::http::{self, HttpConfig};
+use ggmod::logging::{self, LogTarget};
 use ggmod::modz::LocalCollection;
-use ggmod::tui::run_tui;
+use ggmod::tui::{font_size_arg, run_tui, GraphicsConfig, ImageProtocol};
 use log::LevelFilter;
 
 #[derive(Parser)]
@@ -11,9 +18,39 @@ struct Cli {
     name: Option<String>,
 
     /// Provide more debugging information
-    #[arg(short, long, action = clap::ArgAction::Count)]
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
     verbose: u8,
 
+    /// Write logs to this file instead of the ggmod state directory
+    #[arg(long, global = true, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
+    /// Write logs to stderr instead of a file, best kept away from the TUI
+    #[arg(long, global = true, conflicts_with = "log_file")]
+    log_stderr: bool,
+
+    /// Never touch the network, only use cached GameBanana responses
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// User-Agent sent with every request
+    #[arg(long, global = true, env = "GGMOD_USER_AGENT", value_name = "UA", value_parser = http::user_agent_arg)]
+    user_agent: Option<String>,
+
+    /// How the TUI draws previews
+    #[arg(
+        long,
+        global = true,
+        env = "GGMOD_IMAGE_PROTOCOL",
+        value_enum,
+        default_value_t
+    )]
+    image_protocol: ImageProtocol,
+
+    /// Terminal cell size in pixels, e.g. 8x16, asked from the terminal by default
+    #[arg(long, global = true, env = "GGMOD_FONT_SIZE", value_name = "WxH", value_parser = font_size_arg)]
+    font_size: Option<(u16, u16)>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -25,83 +62,175 @@ enum Commands {
         /// Also install or no
         #[arg(short, long)]
         install: bool,
-        /// Mod ID
-        mod_id: usize,
+        /// Pick a file by its listed index, file name or GameBanana file id
+        #[arg(short, long, value_name = "INDEX|NAME|ID", conflicts_with_all = ["latest", "largest"])]
+        file: Option<String>,
+        /// Pick the most recently added file
+        #[arg(long, conflicts_with = "largest")]
+        latest: bool,
+        /// Pick the largest file
+        #[arg(long)]
+        largest: bool,
+        /// Never prompt, falls back to the latest file if nothing else was picked
+        #[arg(short, long)]
+        yes: bool,
+        /// How many mods to download at once
+        #[arg(short, long, default_value_t = 4)]
+        jobs: usize,
+        /// Mod IDs
+        #[arg(required = true)]
+        mod_ids: Vec<usize>,
+    },
+    /// Puts mods inside GGST mod folder
+    Install {
+        #[command(flatten)]
+        query: ModQuery,
     },
-    /// Puts mod inside GGST mod folder
-    Install { mod_id: usize },
 
     /// Can be re-installed again
-    Uninstall { mod_id: usize },
+    Uninstall {
+        #[command(flatten)]
+        query: ModQuery,
+    },
 
     /// List mods and respective IDs
-    List {},
+    List {
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+
+    /// Show everything about an online mod before downloading it
+    Info {
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+
+        /// Mod ID
+        mod_id: usize,
+    },
 
     /// Search online page
     Search {
-        /// Number of results per page
-        #[arg(short, long)]
-        size: Option<usize>,
+        #[command(flatten)]
+        args: SearchArgs,
+    },
 
-        /// Sort by featured
-        #[arg(short, long)]
-        featured: bool,
+    /// List mod categories, usable with search --category
+    Categories {
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
 
-        /// Sort by popularity
-        #[arg(short, long)]
-        popular: bool,
+    /// Inspect and tidy up downloaded files
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+}
 
-        /// Sort by time
-        #[arg(short, long)]
-        recent: bool,
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Show disk usage and files no longer used by any mod
+    Stats,
 
-        /// Page no. starting from 1
-        page: usize,
+    /// Remove files no longer used by any mod
+    Clean,
 
-        /// Search by name
-        name: Option<String>,
+    /// Also remove archives, gallery images and API responses that can be fetched again
+    Prune {
+        /// Only touch files older than this many days
+        #[arg(long, value_name = "DAYS")]
+        older_than: u64,
     },
 }
 
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
-    simple_logging::log_to_file(
-        "log.txt",
-        match cli.verbose {
-            0 => LevelFilter::Off,
-            1 => LevelFilter::Info,
-            2 => LevelFilter::Debug,
-            _ => LevelFilter::Trace,
-        },
-    )
-    .expect("Couldn't setup logging");
-    let collection = LocalCollection::new();
+    setup_logging(&cli);
+    http::set_offline(cli.offline);
+    http::configure(HttpConfig {
+        user_agent: cli
+            .user_agent
+            .clone()
+            .unwrap_or(HttpConfig::default().user_agent),
+        ..Default::default()
+    });
+    if let Err(err) = run(&cli).await {
+        process::exit(report(&err, cli.verbose));
+    }
+}
+
+fn setup_logging(cli: &Cli) {
+    let level = match cli.verbose {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    let target = match (&cli.log_file, cli.log_stderr) {
+        (_, true) => Ok(LogTarget::Stderr),
+        (Some(path), _) => Ok(LogTarget::File(path.clone())),
+        (None, false) => log_path().map(LogTarget::File),
+    };
+    if let Err(e) = target.and_then(|target| logging::init(target, level)) {
+        eprintln!("warning: logging disabled, {e}");
+    }
+}
+
+/// Collection is dropped (and so written) by the time this returns
+async fn run(cli: &Cli) -> Result<()> {
+    let mut collection = LocalCollection::load()?;
     match &cli.command {
-        Some(Commands::Download { mod_id, install }) => {
-            download(collection, *mod_id, *install).await
-        }
-        Some(Commands::Install { mod_id }) => install(collection, *mod_id),
-        Some(Commands::Uninstall { mod_id }) => uninstall(collection, *mod_id),
-        Some(Commands::List {}) => list_all(collection),
-        Some(Commands::Search {
-            page,
-            size: page_size,
-            name,
-            featured,
-            popular,
-            recent,
+        Some(Commands::Download {
+            mod_ids,
+            install,
+            file,
+            latest,
+            largest,
+            yes,
+            jobs,
         }) => {
-            search(
-                *page,
-                *page_size,
-                name.clone(),
-                *featured,
-                *popular,
-                *recent,
-            )
-            .await
+            let selector = match (file, latest, largest) {
+                (Some(pick), _, _) => FileSelector::Pick(pick.clone()),
+                (None, true, _) => FileSelector::Latest,
+                (None, _, true) => FileSelector::Largest,
+                (None, false, false) if *yes => FileSelector::Latest,
+                (None, false, false) => FileSelector::Prompt,
+            };
+            let ids = mod_ids.clone();
+            download(&mut collection, ids, selector, *install, *jobs).await
+        }
+        Some(Commands::Install { query }) => install(&mut collection, query),
+        Some(Commands::Uninstall { query }) => uninstall(&mut collection, query),
+        Some(Commands::List { format }) => list_all(&collection, *format),
+        Some(Commands::Info { mod_id, format }) => info(&collection, *mod_id, *format).await,
+        Some(Commands::Search { args }) => search(args).await,
+        Some(Commands::Categories { format }) => categories(*format).await,
+        Some(Commands::Cache { action }) => match action {
+            CacheAction::Stats => cache_stats(&collection),
+            CacheAction::Clean => cache_clean(&collection),
+            CacheAction::Prune { older_than } => cache_prune(&collection, *older_than),
+        },
+        None => {
+            let graphics = GraphicsConfig {
+                protocol: cli.image_protocol,
+                font_size: cli.font_size,
+            };
+            run_tui(collection, graphics).await
         }
-        None => run_tui(collection).await,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Cli;
+    use clap::CommandFactory;
+
+    #[test]
+    fn cli_is_valid() {
+        Cli::command().debug_assert();
     }
 }
@@ -1,13 +1,21 @@
-use crate::{ensure_sig_file, gamebanana::models::modpage::GBModPage, ggst_path, registry};
+use crate::{
+    ensure_sig_file,
+    error::GGModError,
+    gamebanana::{
+        http::Progress,
+        models::{alternate_source::GBAlternateSource, modpage::GBModPage, submitter::GBSubmitter},
+    },
+    ggst_path, registry,
+};
 
 use anyhow::Result;
-use log::{info, trace};
+use log::{info, trace, warn};
 use ratatui::{
     style::{Color, Stylize},
     widgets::Row,
 };
 use serde::{Deserialize, Serialize};
-use std::{fs, path};
+use std::{fs, io, path};
 
 type MutModClosure = dyn FnMut(&mut Mod) -> Result<()>;
 
@@ -17,34 +25,50 @@ pub struct LocalCollection {
     pub mods: Vec<Mod>,
 }
 
-impl Default for LocalCollection {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 /// Use this for managing mods locally stored
 impl LocalCollection {
-    pub fn new() -> LocalCollection {
-        let path = registry().unwrap_or_default();
+    /// Errors instead of falling back to an empty collection, which would never be
+    /// written back and lose whatever is downloaded or toggled in the meantime
+    pub fn load() -> Result<LocalCollection> {
+        let path = registry()?;
         trace!("New collection instance");
-        LocalCollection {
-            registry_path: path.clone(),
-            mods: Self::load_mods(&path).unwrap_or_default(),
-        }
+        Ok(LocalCollection {
+            mods: Self::load_mods(&path)?,
+            registry_path: path,
+        })
     }
 
-    fn load_mods(path: &path::PathBuf) -> Option<Vec<Mod>> {
-        let file = fs::OpenOptions::new().read(true).open(path).unwrap();
-        Some(serde_json::from_reader(file).unwrap())
+    fn load_mods(path: &path::PathBuf) -> Result<Vec<Mod>> {
+        let registry_err = |reason: String| GGModError::Registry {
+            path: path.clone(),
+            reason,
+        };
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .open(path)
+            .map_err(|e| registry_err(e.to_string()))?;
+        Ok(serde_json::from_reader(file).map_err(|e| registry_err(e.to_string()))?)
     }
 
     pub async fn register_online_mod(&mut self, gbmod: GBModPage, idx: usize) -> Result<()> {
-        let new_mod = Mod::build(gbmod, idx).await?;
-        self.mods.push(new_mod);
+        let new_mod = Mod::build(gbmod, idx, &Progress::default()).await?;
+        self.register(new_mod);
         Ok(())
     }
 
+    /// Downloading a file again replaces its entry rather than listing it twice
+    pub fn register(&mut self, new_mod: Mod) {
+        let same = |m: &&mut Mod| m.id == new_mod.id && m.variant == new_mod.variant;
+        match self.mods.iter_mut().find(same) {
+            Some(old) => {
+                let staged = old.staged || new_mod.staged;
+                *old = new_mod;
+                old.staged = staged;
+            }
+            None => self.mods.push(new_mod),
+        }
+    }
+
     pub fn apply_on_mod(&mut self, id: usize, mut closure: Box<MutModClosure>) -> Result<()> {
         for m in &mut self.mods {
             if m.id == id {
@@ -71,14 +95,16 @@ impl LocalCollection {
             .open(&self.registry_path)
             .ok()?;
         file.set_len(0).ok()?;
-        trace!("Drop LocalCollection, write to {:?}", &self.registry_path);
+        trace!(path:? = self.registry_path; "Drop LocalCollection, write registry");
         serde_json::to_writer(file, &self.mods).ok()
     }
 }
 
 impl Drop for LocalCollection {
     fn drop(&mut self) {
-        self.write_mods();
+        if self.write_mods().is_none() {
+            warn!(path:? = self.registry_path; "Couldn't write registry");
+        }
     }
 }
 
@@ -92,45 +118,83 @@ pub struct Mod {
     pub description: String,
     pub staged: bool,
     pub is_nsfw: bool,
+    #[serde(default)]
+    pub submitter: Option<GBSubmitter>,
+    #[serde(default)]
+    pub profile_url: String,
+    /// Category above `character`, e.g. Skins
+    #[serde(default)]
+    pub super_category: Option<String>,
+    #[serde(default)]
+    pub date_added: usize,
+    #[serde(default)]
+    pub date_updated: usize,
+    #[serde(default)]
+    pub alternate_sources: Vec<GBAlternateSource>,
+    /// Cached preview image, so it can be shown without a connection. Only the first
+    /// one is fetched since that's all the manage view shows
+    #[serde(default)]
+    pub previews: Vec<path::PathBuf>,
 }
 
 impl Mod {
-    async fn build(gbmod: GBModPage, idx: usize) -> Result<Mod> {
+    pub async fn build(gbmod: GBModPage, idx: usize, progress: &Progress) -> Result<Mod> {
+        let path = gbmod.download_file(idx, progress).await?;
+        let previews = gbmod.download_preview().await.into_iter().collect();
         let m = Mod {
             id: gbmod.row,
             character: gbmod.category.name.clone(),
-            path: gbmod.download_file(idx).await?,
+            path,
             name: gbmod.name,
             variant: gbmod.files[idx].file.clone(),
             description: gbmod.description,
             staged: false,
             is_nsfw: gbmod.is_nsfw,
+            submitter: gbmod.submitter,
+            profile_url: gbmod.profile_url,
+            super_category: gbmod.super_category.map(|c| c.name),
+            date_added: gbmod.date_added,
+            date_updated: gbmod.date_updated,
+            alternate_sources: gbmod.alternate_file_sources,
+            previews,
         };
         Ok(m)
     }
 
+    pub fn path(&self) -> &path::Path {
+        &self.path
+    }
+
     pub fn stage(&mut self) -> Result<()> {
-        info!("Staging {}", self.name);
-        let dest = ggst_path().unwrap_or_default().join(self.id.to_string());
+        info!(mod_id = self.id, name:% = self.name; "Staging");
+        let dest = game_mods_path()?.join(self.id.to_string());
         dircpy::copy_dir(&self.path, &dest)?;
-        ensure_sig_file(&dest)?;
+        ensure_sig_file(&dest).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => GGModError::GameNotFound(e.to_string()).into(),
+            _ => anyhow::Error::from(e),
+        })?;
         self.staged = true;
         Ok(())
     }
 
     pub fn unstage(&mut self) -> Result<()> {
-        info!("Unstaging {}", self.name);
-        fs::remove_dir_all(ggst_path().unwrap_or_default().join(self.id.to_string()))?;
+        info!(mod_id = self.id, name:% = self.name; "Unstaging");
+        fs::remove_dir_all(game_mods_path()?.join(self.id.to_string()))?;
         self.staged = false;
         Ok(())
     }
 }
 
+fn game_mods_path() -> Result<path::PathBuf> {
+    Ok(ggst_path().map_err(|e| GGModError::GameNotFound(e.to_string()))?)
+}
+
 impl From<Mod> for Row<'_> {
     fn from(value: Mod) -> Self {
         let row = Row::new(vec![
             value.name.clone(),
             value.character.clone(),
+            value.submitter.map(|s| s.name).unwrap_or_default(),
             value.variant.clone(),
         ]);
         if value.is_nsfw {
@@ -1,6 +1,11 @@
 mod app;
+mod crash;
+mod graphics;
 mod handler;
+mod notify;
 mod state;
+mod tasks;
 mod termination;
 mod ui;
+pub use graphics::{font_size_arg, GraphicsConfig, ImageProtocol};
 pub use handler::run_tui;
@@ -1,23 +1,36 @@
-use std::{cell::RefCell, path::PathBuf};
+use std::{cell::RefCell, mem, path::PathBuf, time::Duration};
 
-use indexmap::IndexMap;
-use log::info;
+use log::{info, trace, warn};
 use ratatui::widgets::TableState;
 use ratatui_image::{picker::Picker, protocol::StatefulProtocol};
 use strum::{EnumIter, IntoEnumIterator};
 use throbber_widgets_tui::ThrobberState;
 
 use crate::{
+    error::classify,
     gamebanana::{
         builder::{FeedFilter, FeedFilterIter, TypeFilter, TypeFilterIter},
-        models::search_result::GBSearchEntry,
+        http,
+        models::{category::GBModCategory, search_result::GBSearchEntry},
     },
     modz::{LocalCollection, Mod},
 };
 
 use anyhow::Result;
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{
+    graphics::cells_to_pixels,
+    notify::Notifications,
+    state::{
+        Categories, CyclicState, Detail, Downloads, Gallery, Itemized, JobState, LocalItems,
+        LocalPreview, OnlineItems, PopupItems, Slide, THUMB_CELLS,
+    },
+    tasks::{Envelope, Message, TaskId, TaskKind, Tasks},
+};
 
-use super::state::{Categories, CyclicState, Itemized, LocalItems, OnlineItems, PopupItems};
+/// Wait for the cursor to settle before loading details, scrolling stays cheap
+const DETAIL_DELAY: Duration = Duration::from_millis(250);
 
 #[derive(Copy, Clone)]
 pub enum ViewDir {
@@ -31,12 +44,21 @@ pub enum View {
     Browse,
 }
 
+/// Whether GameBanana is reachable, local management works regardless
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Connectivity {
+    Connecting,
+    Online,
+    Offline,
+}
+
 #[derive(EnumIter, PartialEq, PartialOrd)]
 pub enum Window {
     Search,
     Main,
     Category,
     Section,
+    Downloads,
 }
 
 /// Basically a container that holds state from state.rs and acts differently based on
@@ -44,51 +66,249 @@ pub enum Window {
 pub struct App {
     collection: LocalCollection,
     page: usize,
-    gallery_page: usize,
     pub popup_items: PopupItems,
     pub online_items: OnlineItems,
     pub staged_items: LocalItems,
     pub unstaged_items: LocalItems,
     pub categories: Categories,
+    pub downloads: Downloads,
+    /// Full page of the highlighted browse entry, once loaded
+    pub detail: Option<Box<Detail>>,
+    pub detail_scroll: u16,
     pub section: CyclicState<TypeFilterIter, TypeFilter>,
     pub cursor: Option<usize>,
     pub view: View,
     pub window: CyclicState<WindowIter, Window>,
     pub sort: CyclicState<FeedFilterIter, FeedFilter>,
-    pub image_states: IndexMap<PathBuf, RefCell<StatefulProtocol>>,
+    pub gallery: Gallery,
+    pub local_preview: LocalPreview,
+    picker: Picker,
     pub throbber_state: Option<ThrobberState>,
+    pub connectivity: Connectivity,
+    pub tasks: Tasks,
+    pub notifications: Notifications,
+    /// Quit was asked for once while downloads were left
+    quit_pending: bool,
 }
 
 impl App {
-    pub async fn new(collection: LocalCollection) -> App {
+    /// Doesn't touch the network, results of background work come back through `tx`
+    pub fn new(collection: LocalCollection, tx: UnboundedSender<Envelope>, picker: Picker) -> App {
         let mut this = App {
             collection,
             popup_items: PopupItems::default(),
             online_items: OnlineItems::default(),
             staged_items: LocalItems::new(Vec::new()),
             unstaged_items: LocalItems::new(Vec::new()),
-            categories: Categories::new().await,
+            categories: Categories::default(),
+            downloads: Downloads::default(),
+            detail: None,
+            detail_scroll: 0,
             section: CyclicState::new(TypeFilter::iter(), TypeFilter::Skin),
             cursor: None,
             view: View::Manage(ViewDir::Left),
             window: CyclicState::new(Window::iter(), Window::Search),
             sort: CyclicState::new(FeedFilter::iter(), FeedFilter::Recent),
-            page: 0,
-            gallery_page: 0,
-            image_states: IndexMap::new(),
+            page: 1,
+            gallery: Gallery::default(),
+            local_preview: LocalPreview::default(),
+            picker,
             throbber_state: None,
+            connectivity: Connectivity::Connecting,
+            tasks: Tasks::new(tx),
+            notifications: Notifications::default(),
+            quit_pending: false,
         };
         this.reregister();
         this
     }
 
+    /// Takes the outcome of (re)loading categories, our canary for being online
+    pub fn connection_result(&mut self, categories: Result<Vec<GBModCategory>>) {
+        match categories {
+            Ok(cats) => {
+                self.categories.refresh(cats);
+                let was_offline = self.connectivity == Connectivity::Offline;
+                self.connectivity = if http::is_offline() {
+                    Connectivity::Offline
+                } else {
+                    Connectivity::Online
+                };
+                if was_offline && self.is_online() {
+                    self.notifications.info("Back online");
+                }
+            }
+            Err(e) => self.report(Err(e)),
+        }
+    }
+
+    /// Toasts failed actions, network failures put the online panes into offline mode.
+    /// Failed reconnects while already offline are only logged
+    pub fn report(&mut self, res: Result<()>) {
+        let Err(e) = res else { return };
+        if !classify(&e).is_unreachable() {
+            self.notifications.error(format!("{e:#}"));
+        } else if self.connectivity == Connectivity::Offline {
+            trace!("Still offline: {e:#}");
+        } else {
+            self.connectivity = Connectivity::Offline;
+            self.notifications
+                .warn(format!("Offline, local mods still work: {e:#}"));
+        }
+    }
+
+    pub fn is_online(&self) -> bool {
+        self.connectivity == Connectivity::Online
+    }
+
+    /// Appended to titles of panes that need GameBanana
+    pub fn connectivity_label(&self) -> &str {
+        match self.connectivity {
+            Connectivity::Online => "",
+            Connectivity::Connecting => " - connecting..",
+            Connectivity::Offline if http::is_offline() => " - offline",
+            Connectivity::Offline => " - offline, retrying",
+        }
+    }
+
+    /// Applies the result of a background task, stale results are dropped
+    pub fn update(&mut self, id: TaskId, msg: Message) {
+        if !self.tasks.finish(id) {
+            trace!(task:? = id.kind; "Dropping result of replaced task");
+            return;
+        }
+        match msg {
+            Message::Categories(res) => {
+                let was_online = self.is_online();
+                self.connection_result(res);
+                if !was_online && self.is_online() && self.online_items.is_empty() {
+                    self.reload_online();
+                }
+            }
+            Message::SearchPage(Ok(results)) => {
+                self.page = results.info.page;
+                self.online_items.show(results);
+                self.request_detail();
+            }
+            Message::SearchPage(Err(e)) => {
+                self.page = self.online_items.page_info.as_ref().map_or(1, |i| i.page);
+                self.report(Err(e));
+            }
+            Message::ModPage(loaded) => match *loaded {
+                (entry, Ok(page)) => {
+                    info!("Popup open {:?}", entry);
+                    self.gallery = Gallery::new(entry.preview_media.clone());
+                    self.popup_items = PopupItems::new(entry, page.files);
+                    self.load_gallery();
+                }
+                (_, Err(e)) => self.report(Err(e)),
+            },
+            Message::Detail(Ok(page)) => {
+                self.detail = Some(page);
+                self.detail_scroll = 0;
+            }
+            Message::Detail(Err(e)) => self.report(Err(e)),
+            Message::Registered(res) => {
+                if let TaskKind::Download(job_id) = id.kind {
+                    self.download_finished(job_id, res);
+                }
+            }
+            Message::Image(res) => self.image_loaded(id.kind, res),
+            Message::Panicked(msg) => {
+                let what = format!("{:?}", id.kind).to_lowercase();
+                self.notifications
+                    .error(format!("Crashed during {what}: {msg}"));
+            }
+        }
+        self.sync_throbber();
+    }
+
+    fn spawn<F>(&mut self, kind: TaskKind, fut: F)
+    where
+        F: std::future::Future<Output = Message> + Send + 'static,
+    {
+        self.tasks.spawn(kind, fut);
+        self.sync_throbber();
+    }
+
+    /// Spins while anything runs in the background
+    fn sync_throbber(&mut self) {
+        if self.tasks.is_busy() {
+            self.throbber_state
+                .get_or_insert_with(ThrobberState::default);
+        } else {
+            self.throbber_state = None;
+        }
+    }
+
+    /// Tries loading categories, which doubles as checking whether we're online
+    pub fn connect(&mut self) {
+        self.spawn(TaskKind::Connect, async {
+            Message::Categories(Categories::fetch().await)
+        });
+    }
+
+    /// Reconnects unless online, already trying, or stuck with the cache by --offline
+    pub fn retry_connection(&mut self) {
+        let given_up = http::is_offline() && self.connectivity == Connectivity::Offline;
+        if !self.is_online() && !given_up && !self.tasks.is_running(TaskKind::Connect) {
+            self.connect();
+        }
+    }
+
+    /// Stops searches and page loads in flight, false if there was nothing to stop
+    pub fn cancel_pending(&mut self) -> bool {
+        let pending = self.tasks.cancel_matching(|k| {
+            matches!(
+                k,
+                TaskKind::Search
+                    | TaskKind::ModPage
+                    | TaskKind::Detail
+                    | TaskKind::Slide(_)
+                    | TaskKind::Thumb(_)
+            )
+        });
+        self.gallery.forget_loading();
+        self.sync_throbber();
+        pending
+    }
+
+    /// Quitting throws away the download queue, so with jobs left the first ask only
+    /// warns about it and the second one goes through
+    pub fn confirm_quit(&mut self) -> bool {
+        let active = self.downloads.active();
+        if active == 0 || self.quit_pending {
+            return true;
+        }
+        self.quit_pending = true;
+        self.notifications.warn(format!(
+            "{active} download(s) will be cancelled, quit again to confirm"
+        ));
+        false
+    }
+
+    /// Anything but quitting again takes the quit back
+    pub fn forget_quit(&mut self) {
+        self.quit_pending = false;
+    }
+
     pub fn throb(&mut self) {
         self.throbber_state.iter_mut().for_each(|s| s.calc_next());
     }
 
-    pub async fn open_popup(&mut self, entry: GBSearchEntry) {
-        self.popup_items = PopupItems::new(entry).await;
-        self.request_gallery_images().await;
+    pub fn open_popup(&mut self, entry: GBSearchEntry) {
+        self.spawn(TaskKind::ModPage, async move {
+            let page = entry.mod_page().await;
+            Message::ModPage(Box::new((entry, page)))
+        });
+    }
+
+    pub fn close_popup(&mut self) {
+        self.tasks
+            .cancel_matching(|k| matches!(k, TaskKind::Slide(_) | TaskKind::Thumb(_)));
+        self.popup_items.clear();
+        self.gallery = Gallery::default();
+        self.sync_throbber();
     }
 
     pub fn reregister(&mut self) {
@@ -96,6 +316,41 @@ impl App {
         let unstaged = self.collection.filter_and_copy_by(Box::new(|m| !m.staged));
         self.staged_items.refresh(staged);
         self.unstaged_items.refresh(unstaged);
+        self.request_local_preview();
+    }
+
+    /// Decodes the highlighted local mod's first preview, straight from the cache so
+    /// it works offline too
+    fn request_local_preview(&mut self) {
+        let View::Manage(dir) = self.view else {
+            return;
+        };
+        let path = self
+            .local_items(dir)
+            .select()
+            .and_then(|m| m.previews.first().cloned());
+        if path == self.local_preview.path {
+            return;
+        }
+        self.tasks.cancel(TaskKind::LocalPreview);
+        self.local_preview = LocalPreview {
+            image: Slide::Pending,
+            path: path.clone(),
+        };
+        if let Some(path) = path {
+            self.local_preview.image = Slide::Loading;
+            let (picker, max) = (self.picker, self.screen_pixels());
+            self.spawn(TaskKind::LocalPreview, async move {
+                Message::Image(decode(path, picker, max).await)
+            });
+        }
+        self.sync_throbber();
+    }
+
+    /// Nothing gets drawn bigger than the terminal, no point decoding more than that
+    fn screen_pixels(&self) -> (u32, u32) {
+        let screen = crossterm::terminal::size().unwrap_or((80, 24));
+        cells_to_pixels(self.picker, screen)
     }
 
     pub fn local_items_mut(&mut self, dir: ViewDir) -> &mut LocalItems {
@@ -115,16 +370,65 @@ impl App {
     // INFO: Apparently a necessary evil, even though it's shit it's also quite simple
     pub fn next(&mut self) {
         match self.view {
-            View::Manage(dir) => self.local_items_mut(dir).next(),
-            View::Browse => self.online_items.next(),
+            View::Manage(dir) => {
+                self.local_items_mut(dir).next();
+                self.request_local_preview();
+            }
+            View::Browse => {
+                self.online_items.next();
+                self.request_detail();
+            }
         }
     }
 
     pub fn previous(&mut self) {
         match self.view {
-            View::Manage(dir) => self.local_items_mut(dir).previous(),
-            View::Browse => self.online_items.previous(),
+            View::Manage(dir) => {
+                self.local_items_mut(dir).previous();
+                self.request_local_preview();
+            }
+            View::Browse => {
+                self.online_items.previous();
+                self.request_detail();
+            }
+        }
+    }
+
+    /// Loads the page of the highlighted browse entry unless it's already showing
+    fn request_detail(&mut self) {
+        let Some(entry) = self.online_items.select().cloned() else {
+            self.detail = None;
+            return;
+        };
+        if self
+            .detail
+            .as_ref()
+            .is_some_and(|d| d.page.row == entry.row)
+        {
+            return;
         }
+        self.spawn(TaskKind::Detail, async move {
+            tokio::time::sleep(DETAIL_DELAY).await;
+            let page = entry.mod_page().await;
+            Message::Detail(page.map(|page| Box::new(Detail::new(page))))
+        });
+    }
+
+    /// None when the mod isn't downloaded, otherwise whether any of its files is staged
+    pub fn local_status(&self, mod_id: usize) -> Option<bool> {
+        self.collection
+            .mods
+            .iter()
+            .filter(|m| m.id == mod_id)
+            .map(|m| m.staged)
+            .reduce(|a, b| a || b)
+    }
+
+    pub fn scroll_detail(&mut self, down: bool) {
+        self.detail_scroll = match down {
+            true => self.detail_scroll.saturating_add(1),
+            false => self.detail_scroll.saturating_sub(1),
+        };
     }
 
     pub fn type_search(&mut self, c: char) {
@@ -165,6 +469,7 @@ impl App {
             }
         }
         self.reset_cursor();
+        self.request_local_preview();
     }
 
     pub fn toggle_sides(&mut self) {
@@ -173,59 +478,153 @@ impl App {
             View::Manage(ViewDir::Right) => self.view = View::Manage(ViewDir::Left),
             View::Browse => (),
         }
+        self.request_local_preview();
     }
 
-    pub async fn select(&mut self) {
+    pub fn select(&mut self) -> Result<()> {
         if !self.popup_items.is_empty() {
-            if let Some(idx) = self.popup_items.select_idx() {
-                let entry = self.popup_items.entry.clone();
-                self.collection
-                    .register_online_mod(entry.unwrap().mod_page().await.unwrap(), idx)
-                    .await
-                    .unwrap();
-                self.popup_items.clear();
-                self.image_states.clear();
-                self.reregister();
-                return;
-            }
-        }
-        match self.view {
-            View::Manage(dir) => {
-                if let Some(m) = self.local_items(dir).select() {
-                    self.collection.toggle(m.id).unwrap();
+            if let (Some(file), Some(entry)) = (
+                self.popup_items.select().cloned(),
+                self.popup_items.entry.clone(),
+            ) {
+                let idx = self.popup_items.select_idx().unwrap_or_default();
+                let name = format!("{} - {}", entry.name, file.file);
+                if self.downloads.push(entry, idx, file.file) {
+                    self.notifications.info(format!("Queued {name}"));
+                    self.start_downloads();
+                } else {
+                    self.notifications.warn(format!("{name} is already queued"));
                 }
+                self.close_popup();
+                return Ok(());
             }
+        }
+        let res = match self.view {
+            View::Manage(dir) => match self.local_items(dir).select().cloned() {
+                Some(m) => self.collection.toggle(m.id).map(|_| {
+                    let done = if m.staged { "Uninstalled" } else { "Installed" };
+                    self.notifications.info(format!("{done} {}", m.name));
+                }),
+                None => Ok(()),
+            },
             View::Browse => {
                 if let Some(entry) = self.online_items.select() {
-                    let other = entry.clone();
-                    self.open_popup(entry.clone()).await;
-                    info!("Popup open {:?}", other);
+                    self.open_popup(entry.clone());
                 }
+                Ok(())
             }
-        }
+        };
         self.reregister();
+        res
     }
 
-    pub async fn search(&mut self) -> Result<()> {
-        //TODO: we probably need message passing
-        //self.throbber_state = Some(ThrobberState::default());
-        info!("START THROBBING, need THROB");
-        let res = match self.view {
-            View::Manage(_) => Ok(()),
-            View::Browse => {
-                self.online_items
-                    .search(
-                        self.section.item.clone(),
-                        self.sort.item.clone(),
-                        self.categories.select().map(|cat| cat.row),
-                        self.page,
-                    )
-                    .await
+    /// Starts queued downloads while there's room for them
+    fn start_downloads(&mut self) {
+        for job in self.downloads.start_queued() {
+            let (entry, idx, progress) = (job.entry, job.file_idx, job.progress);
+            self.spawn(TaskKind::Download(job.id), async move {
+                let res = match entry.mod_page().await {
+                    Ok(page) => Mod::build(page, idx, &progress).await.map(Box::new),
+                    Err(e) => Err(e),
+                };
+                Message::Registered(res)
+            });
+        }
+    }
+
+    /// Registers the mod right away so it shows up in the manage tables
+    fn download_finished(&mut self, job_id: usize, res: Result<Box<Mod>>) {
+        let state = match res {
+            Ok(m) => {
+                self.notifications.info(format!("Downloaded {}", m.name));
+                self.collection.register(*m);
+                self.reregister();
+                JobState::Done
+            }
+            Err(e) => {
+                let state = JobState::Failed(format!("{e}"));
+                self.report(Err(e));
+                state
             }
         };
-        info!("STOP THROBBING");
-        //self.throbber_state.take();
-        res
+        if let Some(job) = self.downloads.get_mut(job_id) {
+            job.state = state;
+        }
+        self.start_downloads();
+    }
+
+    pub fn cancel_download(&mut self) {
+        let Some(job) = self.downloads.select().cloned() else {
+            return;
+        };
+        if matches!(job.state, JobState::Queued | JobState::Running) {
+            self.tasks.cancel(TaskKind::Download(job.id));
+            if let Some(job) = self.downloads.get_mut(job.id) {
+                job.state = JobState::Cancelled;
+            }
+            self.sync_throbber();
+            self.start_downloads();
+        }
+    }
+
+    pub fn retry_download(&mut self) {
+        let Some(id) = self.downloads.select().map(|job| job.id) else {
+            return;
+        };
+        if let Some(job) = self.downloads.get_mut(id) {
+            if matches!(job.state, JobState::Failed(_) | JobState::Cancelled) {
+                job.progress.reset();
+                job.state = JobState::Queued;
+            }
+        }
+        self.start_downloads();
+    }
+
+    /// Starts a new online search from page 1 with whatever was typed
+    pub fn search(&mut self) {
+        if let View::Browse = self.view {
+            self.online_items.searched = mem::take(&mut self.online_items.query);
+            self.page = 1;
+            self.reset_cursor();
+        }
+        self.fetch_page()
+    }
+
+    pub fn change_page(&mut self, forward: bool) {
+        if forward && self.online_items.has_next_page() {
+            self.page += 1;
+        } else if !forward && self.page > 1 {
+            self.page -= 1;
+        } else {
+            return;
+        }
+        self.fetch_page()
+    }
+
+    pub fn page_label(&self) -> Option<String> {
+        self.online_items
+            .page_info
+            .as_ref()
+            .map(|info| info.to_string())
+    }
+
+    fn fetch_page(&mut self) {
+        if let View::Browse = self.view {
+            self.reload_online()
+        }
+    }
+
+    /// Refetches the current browse page in the background, whichever view is showing
+    pub fn reload_online(&mut self) {
+        let search = self.online_items.search(
+            self.section.item.clone(),
+            self.sort.item.clone(),
+            self.categories.select().map(|cat| cat.row),
+        );
+        let page = self.page;
+        self.spawn(TaskKind::Search, async move {
+            Message::SearchPage(search.read_page(page).await)
+        });
     }
 
     pub fn help_text(&self) -> &str {
@@ -234,10 +633,20 @@ impl App {
                 "Space - Install / Uninstall from game dir\n\
                  H / L - Switch local/gamebanana mods\n\
                  h / l - local - Switch sides\n\
-                         online - Scroll pages\n\
-                 x - local - Delete mod permanently"
+                         popup - Scroll gallery\n\
+                 [ / ] - online - Previous / next page\n\
+                 J / K - online - Scroll details\n\
+                 x - local - Delete mod permanently\n\
+                 m - Message history\n\
+                 Esc - Cancel loading, quit when idle"
             }
             Window::Category => "j/k - scroll",
+            Window::Downloads => {
+                "j/k - scroll\n\
+                 c - Cancel download\n\
+                 r - Retry download\n\
+                 d - Clear finished"
+            }
             Window::Section => "j/k - scroll",
             Window::Search => {
                 "type and press enter to search\n\
@@ -247,20 +656,13 @@ impl App {
     }
 
     pub fn gallery_prev(&mut self) {
-        if self.gallery_page == 0 {
-            return;
-        };
-        self.gallery_page -= 1;
+        self.gallery.previous();
+        self.load_gallery();
     }
 
     pub fn gallery_next(&mut self) {
-        if self.gallery_page < self.image_states.len() - 1 {
-            self.gallery_page += 1;
-        }
-    }
-
-    pub fn gallery_page(&self) -> usize {
-        self.gallery_page
+        self.gallery.next();
+        self.load_gallery();
     }
 
     pub fn reset_cursor(&mut self) {
@@ -268,45 +670,99 @@ impl App {
         self.cursor = if length == 0 { None } else { Some(length) }
     }
 
-    pub fn remove(&mut self) -> Option<()> {
+    pub fn remove(&mut self) {
         if let View::Manage(dir) = self.view {
             if let Some(chosen) = self.local_items(dir).select() {
                 let pred = |m: &Mod| m.id == chosen.id;
-                let idx = self.collection.mods.iter().position(pred)?;
-                self.collection.mods.remove(idx);
+                match self.collection.mods.iter().position(pred) {
+                    Some(idx) => {
+                        let removed = self.collection.mods.remove(idx);
+                        self.notifications.info(format!("Deleted {}", removed.name));
+                    }
+                    None => self.notifications.warn("Mod is already gone"),
+                }
                 self.reregister();
             }
         }
-        Some(())
     }
 
-    pub async fn request_gallery_images(self: &mut App) {
-        let mut picker = Picker::from_fontsize((8, 12));
-        if let Some(entry) = self.online_items.select() {
-            // TODO: Make this request more images on demand or something
-            let downloaded_media = entry.download_media(1).await;
-            if let Some(path) = downloaded_media.get(self.gallery_page()) {
-                self.check_insert_image(&mut picker, path);
-            }
+    /// Fetches the current slide and the next one, and every thumbnail not loaded yet
+    pub fn load_gallery(&mut self) {
+        let Some(mod_id) = self.popup_items.entry.as_ref().map(|e| e.row) else {
+            return;
+        };
+        let picker = self.picker;
+        let (slide_px, thumb_px) = (self.screen_pixels(), cells_to_pixels(picker, THUMB_CELLS));
+        for idx in self.gallery.wanted() {
+            self.gallery.slides[idx] = Slide::Loading;
+            let media = self.gallery.media[idx].clone();
+            self.spawn(TaskKind::Slide(idx), async move {
+                let res = match media.fetch(mod_id).await {
+                    Ok(path) => decode(path, picker, slide_px).await,
+                    Err(e) => Err(e),
+                };
+                Message::Image(res)
+            });
+        }
+        for idx in self.gallery.wanted_thumbs() {
+            self.gallery.thumbs[idx] = Slide::Loading;
+            let media = self.gallery.media[idx].clone();
+            self.spawn(TaskKind::Thumb(idx), async move {
+                let res = match media.fetch_thumb(mod_id).await {
+                    Ok(path) => decode(path, picker, thumb_px).await,
+                    Err(e) => Err(e),
+                };
+                Message::Image(res)
+            });
         }
     }
 
-    fn check_insert_image(self: &mut App, picker: &mut Picker, path: &PathBuf) {
-        if !self.image_states.contains_key(path) {
-            let dyn_img = image::ImageReader::open(path.clone())
-                .unwrap()
-                .decode()
-                .unwrap()
-                .resize(400, 400, ratatui_image::FilterType::Gaussian);
-            let image = RefCell::new(picker.new_resize_protocol(dyn_img));
-            self.image_states.insert(path.clone(), image);
+    /// Broken thumbnails and local previews are only logged, the panes show they failed
+    fn image_loaded(&mut self, kind: TaskKind, res: Result<Box<StatefulProtocol>>) {
+        let slide = match res {
+            Ok(image) => Slide::Ready(RefCell::new(*image)),
+            Err(e) if matches!(kind, TaskKind::Slide(_)) => {
+                self.report(Err(e.context("Couldn't load preview")));
+                Slide::Failed
+            }
+            Err(e) => {
+                warn!(task:? = kind; "Couldn't load image: {e:#}");
+                Slide::Failed
+            }
+        };
+        let slot = match kind {
+            TaskKind::LocalPreview => Some(&mut self.local_preview.image),
+            TaskKind::Slide(idx) => self.gallery.slides.get_mut(idx),
+            TaskKind::Thumb(idx) => self.gallery.thumbs.get_mut(idx),
+            _ => None,
+        };
+        if let Some(slot) = slot {
+            *slot = slide;
         }
     }
 
-    // TODO: Toasts
-    // + Perf. optimsation
+    // TODO: Perf. optimsation
     // + Sorting tables
     // + Fix sfw searching + images
     // + Mod deletion and modification
     // + Replace builder with derive_builder macro (long)
 }
+
+/// Decodes on the blocking pool, shrinking anything bigger than `max` pixels (keeping the
+/// aspect ratio) so resizing to the render area later stays cheap
+async fn decode(
+    path: PathBuf,
+    picker: Picker,
+    (max_w, max_h): (u32, u32),
+) -> Result<Box<StatefulProtocol>> {
+    tokio::task::spawn_blocking(move || {
+        let mut image = image::ImageReader::open(&path)?
+            .with_guessed_format()?
+            .decode()?;
+        if image.width() > max_w || image.height() > max_h {
+            image = image.resize(max_w, max_h, ratatui_image::FilterType::Triangle);
+        }
+        Ok(Box::new(picker.new_resize_protocol(image)))
+    })
+    .await?
+}
@@ -0,0 +1,61 @@
+use std::{
+    any::Any,
+    backtrace::Backtrace,
+    panic::{self, PanicHookInfo},
+    sync::OnceLock,
+    thread::{self, ThreadId},
+};
+
+use log::error;
+
+use crate::logging::log_file;
+
+/// Thread running the UI loop, tokio catches panics anywhere else and the TUI lives on
+static UI_THREAD: OnceLock<ThreadId> = OnceLock::new();
+
+/// Leaves raw mode and the alternate screen when dropped, unwinding included
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    /// Replaces ratatui's panic hook, which restores the terminal on a panic in any
+    /// thread, even one that only takes down a background task
+    pub fn install() -> Self {
+        let _ = UI_THREAD.set(thread::current().id());
+        panic::set_hook(Box::new(hook));
+        TerminalGuard
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        ratatui::restore();
+        // Back to the default hook, not allowed while panicking
+        if !thread::panicking() {
+            let _ = panic::take_hook();
+        }
+    }
+}
+
+fn hook(info: &PanicHookInfo) {
+    let backtrace = Backtrace::force_capture();
+    error!("{info}\n{backtrace}");
+    log::logger().flush();
+    if UI_THREAD.get() != Some(&thread::current().id()) {
+        return;
+    }
+    ratatui::restore();
+    match log_file() {
+        Some(path) => eprintln!("ggmod crashed, {info}\nBacktrace in {}", path.display()),
+        None => eprintln!("ggmod crashed, {info}\n{backtrace}"),
+    }
+}
+
+/// What a panic was called with, for the ones caught in background tasks
+pub fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    match payload.downcast_ref::<&str>() {
+        Some(s) => s,
+        None => payload
+            .downcast_ref::<String>()
+            .map_or("no message", String::as_str),
+    }
+}
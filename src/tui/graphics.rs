@@ -0,0 +1,102 @@
+use clap::ValueEnum;
+use log::{info, warn};
+use ratatui_image::{
+    picker::{Picker, ProtocolType},
+    FontSize,
+};
+
+/// Rough cell size when the terminal won't tell, only the 1:2 ratio really matters
+const FALLBACK_FONT_SIZE: FontSize = (10, 20);
+
+/// How previews are drawn, auto asks the terminal what it supports
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ImageProtocol {
+    #[default]
+    Auto,
+    /// Unicode half blocks, works everywhere but looks blocky
+    Halfblocks,
+    Sixel,
+    Kitty,
+    Iterm2,
+}
+
+impl ImageProtocol {
+    fn forced(self) -> Option<ProtocolType> {
+        match self {
+            ImageProtocol::Auto => None,
+            ImageProtocol::Halfblocks => Some(ProtocolType::Halfblocks),
+            ImageProtocol::Sixel => Some(ProtocolType::Sixel),
+            ImageProtocol::Kitty => Some(ProtocolType::Kitty),
+            ImageProtocol::Iterm2 => Some(ProtocolType::Iterm2),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GraphicsConfig {
+    pub protocol: ImageProtocol,
+    /// Pixels per cell, asked from the terminal when not set
+    pub font_size: Option<FontSize>,
+}
+
+/// Parses `WxH`, e.g. `8x16`
+pub fn font_size_arg(s: &str) -> Result<FontSize, String> {
+    let err = || format!("{s:?} isn't a WIDTHxHEIGHT font size");
+    let (w, h) = s.split_once(['x', 'X']).ok_or_else(err)?;
+    match (w.trim().parse(), h.trim().parse()) {
+        (Ok(w), Ok(h)) if w > 0 && h > 0 => Ok((w, h)),
+        _ => Err(err()),
+    }
+}
+
+/// Has to run after entering the alternate screen and before reading any input, the
+/// terminal answers through stdin. Anything it can't find out falls back to halfblocks
+pub fn picker(config: GraphicsConfig) -> Picker {
+    let fully_set = config.protocol.forced().is_some() && config.font_size.is_some();
+    let queried = if fully_set {
+        None
+    } else {
+        Picker::from_query_stdio()
+            .inspect_err(|e| warn!("Couldn't query terminal graphics, using halfblocks: {e}"))
+            .ok()
+    };
+    let mut picker = match (queried, config.font_size) {
+        (Some(picker), None) => picker,
+        (Some(queried), Some(font_size)) => {
+            let mut picker = Picker::from_fontsize(font_size);
+            picker.set_protocol_type(queried.protocol_type());
+            picker
+        }
+        (None, font_size) => Picker::from_fontsize(font_size.unwrap_or(FALLBACK_FONT_SIZE)),
+    };
+    if let Some(protocol) = config.protocol.forced() {
+        picker.set_protocol_type(protocol);
+    }
+    info!(
+        protocol:? = picker.protocol_type(),
+        font_size:? = picker.font_size();
+        "Image picker ready"
+    );
+    picker
+}
+
+/// Pixel size of `cols`x`rows` cells, the most an image drawn there can make use of
+pub fn cells_to_pixels(picker: Picker, (cols, rows): (u16, u16)) -> (u32, u32) {
+    let (w, h) = picker.font_size();
+    (cols as u32 * w as u32, rows as u32 * h as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn font_sizes_parse() {
+        assert_eq!(font_size_arg("8x16"), Ok((8, 16)));
+        assert_eq!(font_size_arg(" 7 X 14"), Ok((7, 14)));
+        assert!(font_size_arg("8").is_err());
+        assert!(font_size_arg("0x16").is_err());
+        let picker = Picker::from_fontsize((8, 16));
+        assert_eq!(cells_to_pixels(picker, (10, 2)), (80, 32));
+    }
+}
@@ -1,84 +1,109 @@
-use std::sync::Arc;
+use std::time::Duration;
 
-use ratatui::{
-    crossterm::event::{self, Event, KeyCode},
-    prelude::Backend,
-    Terminal,
+use anyhow::{Context, Result};
+use crossterm::event::{Event, EventStream, KeyCode, KeyEventKind};
+use futures::StreamExt;
+use log::trace;
+use ratatui::{prelude::Backend, Terminal};
+use tokio::{
+    sync::{
+        broadcast::Receiver,
+        mpsc::{self, UnboundedReceiver},
+    },
+    time::{interval, MissedTickBehavior},
 };
-use throbber_widgets_tui::ThrobberState;
-use tokio::sync::{broadcast::Receiver, Mutex};
 
 use crate::modz::LocalCollection;
 
 use super::{
     app::{App, View, Window},
+    crash::TerminalGuard,
+    graphics::{picker, GraphicsConfig},
     state::Itemized,
+    tasks::Envelope,
     termination::Termination,
     ui::show_ui,
 };
 
-type Am<T> = Arc<Mutex<T>>;
+/// Time between attempts at reaching GameBanana while offline
+const RETRY_DELAY: Duration = Duration::from_secs(15);
+/// Frame time of animations, nothing is redrawn on ticks when there's nothing animating
+const TICK_RATE: Duration = Duration::from_millis(100);
 
-pub async fn run_tui(collection: LocalCollection) {
-    let terminal = Arc::new(Mutex::new(ratatui::init()));
-    let app = Arc::new(Mutex::new(App::new(collection).await));
+pub async fn run_tui(collection: LocalCollection, graphics: GraphicsConfig) -> Result<()> {
+    let mut terminal = ratatui::try_init().context("Couldn't set up the terminal")?;
+    let guard = TerminalGuard::install();
+    // Before the event stream starts, the terminal's answers come in over stdin
+    let picker = picker(graphics);
+    let (tx, rx) = mpsc::unbounded_channel();
+    let mut app = App::new(collection, tx, picker);
     let (termination, rx_terminate) = Termination::new();
-    let (app_copy, rx_terminate_copy) = (app.clone(), rx_terminate.resubscribe());
-    tokio::spawn(async move { draw_loop(terminal, app, rx_terminate).await });
-    tokio::spawn(async move { event_loop(app_copy, termination, rx_terminate_copy).await })
-        .await
-        .unwrap();
+    let res = ui_loop(&mut terminal, &mut app, rx, &termination, rx_terminate).await;
+    app.tasks.cancel_all();
+    drop(guard);
+    res.context("TUI stopped")
 }
 
-async fn event_loop(app: Am<App>, term: Termination, mut rx_term: Receiver<usize>) {
-    loop {
-        if rx_term.try_recv().unwrap_or(0) == 1 {
-            ratatui::restore();
-            break;
-        }
-        let mut appref = app.lock().await;
-        appref.throbber_state = Some(ThrobberState::default());
-        handle_event(&mut appref, &term).await;
-        appref.throbber_state.take();
-    }
-}
-
-async fn draw_loop<B: Backend>(
-    terminal: Am<Terminal<B>>,
-    app: Am<App>,
+/// Redraws only after something changed: input, a finished background task, or a tick
+/// while the throbber spins. Also keeps trying to reach GameBanana while offline
+async fn ui_loop<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    mut rx: UnboundedReceiver<Envelope>,
+    term: &Termination,
     mut rx_term: Receiver<usize>,
-) {
+) -> Result<()> {
+    let mut events = EventStream::new();
+    let mut tick = interval(TICK_RATE);
+    tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut retry = interval(RETRY_DELAY);
+    retry.set_missed_tick_behavior(MissedTickBehavior::Delay);
     loop {
-        if rx_term.try_recv().unwrap_or(0) == 1 {
-            ratatui::restore();
-            break;
+        terminal.draw(|f| show_ui(f, app))?;
+        let animating = app.throbber_state.is_some() || app.notifications.has_toasts();
+        trace!(animating; "Frame drawn");
+        tokio::select! {
+            event = events.next() => match event {
+                Some(event) => handle_event(app, &event?, term),
+                None => break,
+            },
+            Some((id, msg)) = rx.recv() => app.update(id, msg),
+            _ = tick.tick(), if animating => app.throb(),
+            _ = retry.tick() => app.retry_connection(),
+            _ = rx_term.recv() => break,
         }
-        let mut appref = app.lock().await;
-        terminal
-            .lock()
-            .await
-            .draw(|f| {
-                show_ui(f, &mut appref);
-            })
-            .unwrap();
     }
+    Ok(())
 }
 
-async fn handle_event(app: &mut App, term: &Termination) {
-    if let Event::Key(key) = event::read().unwrap() {
-        if key.kind == event::KeyEventKind::Release {
+fn handle_event(app: &mut App, event: &Event, term: &Termination) {
+    if let Event::Key(key) = event {
+        if key.kind == KeyEventKind::Release {
+            return;
+        }
+        if !matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+            app.forget_quit();
+        }
+        if app.notifications.show_history {
+            match key.code {
+                KeyCode::Char('j') | KeyCode::Down => app.notifications.scroll_down(),
+                KeyCode::Char('k') | KeyCode::Up => app.notifications.scroll_up(),
+                KeyCode::Char('m' | 'q') | KeyCode::Esc => app.notifications.toggle_history(),
+                _ => (),
+            }
             return;
         }
         if !app.popup_items.is_empty() {
             match key.code {
                 KeyCode::Char('j') => app.popup_items.next(),
                 KeyCode::Char('k') => app.popup_items.previous(),
-                KeyCode::Char('q') => app.popup_items.clear(),
-                KeyCode::Esc => {
-                    app.popup_items.clear();
-                    app.image_states.clear();
+                KeyCode::Char('h') | KeyCode::Left => app.gallery_prev(),
+                KeyCode::Char('l') | KeyCode::Right => app.gallery_next(),
+                KeyCode::Char('q') | KeyCode::Esc => app.close_popup(),
+                KeyCode::Enter => {
+                    let res = app.select();
+                    app.report(res);
                 }
-                KeyCode::Enter => app.select().await,
                 _ => (),
             }
             return;
@@ -87,13 +112,14 @@ async fn handle_event(app: &mut App, term: &Termination) {
             Window::Search => match key.code {
                 KeyCode::Left => app.sort.cycle_back(),
                 KeyCode::Right => app.sort.cycle(),
-                KeyCode::Enter => app.search().await.unwrap(),
+                KeyCode::Enter => app.search(),
                 KeyCode::Backspace => app.backspace(),
                 KeyCode::Char(s) if app.search_query().is_empty() => match s {
                     '1' => app.window.cycle_to(Window::Search),
                     '2' => app.window.cycle_to(Window::Main),
                     '3' => app.window.cycle_to(Window::Category),
                     '4' => app.window.cycle_to(Window::Section),
+                    '5' => app.window.cycle_to(Window::Downloads),
                     other => app.type_search(other),
                 },
                 KeyCode::Char(s) => app.type_search(s),
@@ -104,26 +130,27 @@ async fn handle_event(app: &mut App, term: &Termination) {
                     Window::Main => match key.code {
                         KeyCode::Char('H') => app.toggle_view(),
                         KeyCode::Char('L') => app.toggle_view(),
-                        KeyCode::Char('h') | KeyCode::Left => match app.view {
-                            View::Manage(_) => app.toggle_sides(),
-                            View::Browse => {
-                                if !app.image_states.is_empty() {
-                                    app.gallery_prev()
-                                }
-                            }
-                        },
-                        KeyCode::Char('l') | KeyCode::Right => match app.view {
-                            View::Manage(_) => app.toggle_sides(),
-                            View::Browse => {
-                                if !app.image_states.is_empty() {
-                                    app.gallery_next()
-                                }
-                            }
-                        },
+                        KeyCode::Char('h') | KeyCode::Left => app.toggle_sides(),
+                        KeyCode::Char('l') | KeyCode::Right => app.toggle_sides(),
                         KeyCode::Char('j') | KeyCode::Down => app.next(),
                         KeyCode::Char('k') | KeyCode::Up => app.previous(),
-                        KeyCode::Char('x') => app.remove().unwrap(),
-                        KeyCode::Enter => app.select().await,
+                        KeyCode::Char('x') => app.remove(),
+                        KeyCode::Char('J') if matches!(app.view, View::Browse) => {
+                            app.scroll_detail(true)
+                        }
+                        KeyCode::Char('K') if matches!(app.view, View::Browse) => {
+                            app.scroll_detail(false)
+                        }
+                        KeyCode::Char(']') if matches!(app.view, View::Browse) => {
+                            app.change_page(true)
+                        }
+                        KeyCode::Char('[') if matches!(app.view, View::Browse) => {
+                            app.change_page(false)
+                        }
+                        KeyCode::Enter => {
+                            let res = app.select();
+                            app.report(res);
+                        }
                         _ => (),
                     },
                     Window::Section => match key.code {
@@ -136,6 +163,14 @@ async fn handle_event(app: &mut App, term: &Termination) {
                         KeyCode::Char('k') | KeyCode::Up => app.categories.previous(),
                         _ => (),
                     },
+                    Window::Downloads => match key.code {
+                        KeyCode::Char('j') | KeyCode::Down => app.downloads.next(),
+                        KeyCode::Char('k') | KeyCode::Up => app.downloads.previous(),
+                        KeyCode::Char('c') => app.cancel_download(),
+                        KeyCode::Char('r') => app.retry_download(),
+                        KeyCode::Char('d') => app.downloads.clear_done(),
+                        _ => (),
+                    },
                     _ => (),
                 };
                 match key.code {
@@ -143,13 +178,15 @@ async fn handle_event(app: &mut App, term: &Termination) {
                     KeyCode::Char('2') => app.window.cycle_to(Window::Main),
                     KeyCode::Char('3') => app.window.cycle_to(Window::Category),
                     KeyCode::Char('4') => app.window.cycle_to(Window::Section),
-                    KeyCode::Char('q') => term.exit(),
+                    KeyCode::Char('5') => app.window.cycle_to(Window::Downloads),
+                    KeyCode::Char('m') => app.notifications.toggle_history(),
+                    KeyCode::Char('q') if app.confirm_quit() => term.exit(),
                     _ => (),
                 }
             }
         }
         match key.code {
-            KeyCode::Esc => term.exit(),
+            KeyCode::Esc if !app.cancel_pending() && app.confirm_quit() => term.exit(),
             KeyCode::Tab => app.window.cycle(),
             KeyCode::BackTab => app.window.cycle_back(),
             _ => (),
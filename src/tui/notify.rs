@@ -0,0 +1,157 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use log::{error, info, warn};
+
+/// How long a toast stays up, the message stays in the history after
+pub const TOAST_TIME: Duration = Duration::from_secs(4);
+/// Toasts shown at once, older ones are only in the history
+const MAX_TOASTS: usize = 3;
+const MAX_HISTORY: usize = 200;
+
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Severity::Info => "info",
+            Severity::Warning => "warn",
+            Severity::Error => "error",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub severity: Severity,
+    pub text: String,
+    /// Same message in a row gets counted instead of repeated
+    pub count: usize,
+    pub at: Instant,
+}
+
+impl Notification {
+    pub fn label(&self) -> String {
+        match self.count {
+            1 => self.text.clone(),
+            n => format!("{} (x{n})", self.text),
+        }
+    }
+}
+
+/// Message history, newest last, where the latest few double as toasts
+#[derive(Default)]
+pub struct Notifications {
+    history: VecDeque<Notification>,
+    /// Lines scrolled up from the newest message in the history pane
+    pub scroll: usize,
+    pub show_history: bool,
+}
+
+impl Notifications {
+    pub fn push(&mut self, severity: Severity, text: impl Into<String>) {
+        let text = text.into();
+        match severity {
+            Severity::Info => info!(toast = true; "{text}"),
+            Severity::Warning => warn!(toast = true; "{text}"),
+            Severity::Error => error!(toast = true; "{text}"),
+        }
+        let now = Instant::now();
+        if let Some(last) = self.history.back_mut() {
+            if last.severity == severity && last.text == text {
+                last.count += 1;
+                last.at = now;
+                return;
+            }
+        }
+        self.history.push_back(Notification {
+            severity,
+            text,
+            count: 1,
+            at: now,
+        });
+        if self.history.len() > MAX_HISTORY {
+            self.history.pop_front();
+        }
+    }
+
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(Severity::Info, text)
+    }
+
+    pub fn warn(&mut self, text: impl Into<String>) {
+        self.push(Severity::Warning, text)
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(Severity::Error, text)
+    }
+
+    /// Still fresh messages, oldest first
+    pub fn toasts(&self) -> impl Iterator<Item = &Notification> {
+        let fresh = self
+            .history
+            .iter()
+            .rev()
+            .take(MAX_TOASTS)
+            .take_while(|n| n.at.elapsed() < TOAST_TIME)
+            .count();
+        self.history.iter().skip(self.history.len() - fresh)
+    }
+
+    /// Toasts have to be redrawn away once they expire
+    pub fn has_toasts(&self) -> bool {
+        self.toasts().next().is_some()
+    }
+
+    pub fn history(&self) -> impl DoubleEndedIterator<Item = &Notification> {
+        self.history.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn toggle_history(&mut self) {
+        self.show_history = !self.show_history;
+        self.scroll = 0;
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = (self.scroll + 1).min(self.len().saturating_sub(1));
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn repeats_are_counted() {
+        let mut notes = Notifications::default();
+        notes.error("Offline");
+        notes.error("Offline");
+        notes.info("Downloaded Ky");
+        notes.warn("a");
+        notes.warn("b");
+        assert_eq!(notes.len(), 4);
+        assert_eq!(notes.history().next().unwrap().label(), "Offline (x2)");
+        let toasts: Vec<_> = notes.toasts().map(|n| n.text.as_str()).collect();
+        assert_eq!(toasts, vec!["Downloaded Ky", "a", "b"]);
+        notes.history.iter_mut().for_each(|n| n.at -= TOAST_TIME);
+        assert!(!notes.has_toasts());
+    }
+}
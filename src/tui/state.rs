@@ -1,16 +1,29 @@
 use std::iter::Cycle;
 
-use std::cell::RefCell;
+use std::{cell::RefCell, path::PathBuf};
 
 use log::trace;
-use ratatui::widgets::{Row, TableState};
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Text},
+    widgets::{Row, TableState},
+};
+use ratatui_image::protocol::StatefulProtocol;
 
 use crate::{
     gamebanana::{
         builder::{FeedFilter, SearchBuilder, SearchFilter, TypeFilter},
-        models::{category::GBModCategory, file::GBFile, search_result::GBSearchEntry},
+        http::Progress,
+        models::{
+            category::GBModCategory, file::GBFile, modpage::GBModPage, preview::GBPreviewMedia,
+            search_result::GBSearchEntry,
+        },
+        query::SearchQuery,
+        search::{PageInfo, Search, SearchPage},
+        GAME_ID, ROOT_CATEGORY,
     },
     modz::Mod,
+    tui::ui::html_to_lines,
 };
 
 use anyhow::Result;
@@ -65,41 +78,38 @@ pub trait Itemized {
 #[derive(Default)]
 pub struct OnlineItems {
     pub query: String,
+    /// Query the current results were fetched with, kept around for paging
+    pub searched: String,
+    pub page_info: Option<PageInfo>,
+    pub last_page: bool,
     pub state: RefCell<TableState>,
     pub content: Vec<GBSearchEntry>,
 }
 
 impl OnlineItems {
-    pub async fn search(
-        &mut self,
-        section: TypeFilter,
-        sort: FeedFilter,
-        category: Option<usize>,
-        page: usize,
-    ) -> Result<()> {
-        let search_type = match category {
-            Some(cat_id) if cat_id != 0 => SearchFilter::Category { cat_id },
-            Some(_) | None => {
-                if self.query.is_empty() {
-                    SearchFilter::Game { game_id: 11534 }
-                } else {
-                    SearchFilter::Name {
-                        search: &self.query,
-                        game_id: 11534,
-                    }
-                }
-            }
-        };
-        let search = SearchBuilder::new()
+    /// Search for `searched`, pages are read from it in the background
+    pub fn search(&self, section: TypeFilter, sort: FeedFilter, category: Option<usize>) -> Search {
+        let query = SearchQuery::new()
+            .name(Some(&self.searched))
+            .category(category);
+        trace!("Are we searching categorically: {category:?}");
+        SearchBuilder::new()
             .of_type(section)
             .with_sort(sort)
-            .by_search(search_type)
-            .of_category(category.filter(|id| *id != 0));
-        trace!("Are we searching categorically: {category:?}");
-        let results = search.build().read_page(page).await?;
-        self.refresh(results);
-        self.query.clear();
-        Ok(())
+            .by_search(SearchFilter::Game { game_id: GAME_ID })
+            .with_query(query)
+            .build()
+    }
+
+    pub fn show(&mut self, results: SearchPage) {
+        self.last_page = results.is_last();
+        self.page_info = Some(results.info);
+        self.refresh(results.entries);
+    }
+
+    /// Whether there's a page after the current one as far as we know
+    pub fn has_next_page(&self) -> bool {
+        self.page_info.is_some() && !self.last_page
     }
 }
 
@@ -127,10 +137,10 @@ pub struct PopupItems {
 }
 
 impl PopupItems {
-    pub async fn new(entry: GBSearchEntry) -> Self {
+    pub fn new(entry: GBSearchEntry, files: Vec<GBFile>) -> Self {
         Self {
             state: RefCell::new(TableState::default()),
-            content: entry.mod_page().await.unwrap().files,
+            content: files,
             entry: Some(entry),
         }
     }
@@ -188,20 +198,16 @@ impl Itemized for LocalItems {
     }
 }
 
+/// Starts out empty, filled in once GameBanana answers
+#[derive(Default)]
 pub struct Categories {
     pub state: RefCell<TableState>,
     pub content: Vec<GBModCategory>,
 }
 
 impl Categories {
-    pub async fn new() -> Self {
-        let this = Self {
-            state: RefCell::new(TableState::default()),
-            // TODO: Find out where this magic number come from
-            content: GBModCategory::build(12914).await.unwrap_or_default(),
-        };
-        this.state().borrow_mut().select(Some(0));
-        this
+    pub async fn fetch() -> Result<Vec<GBModCategory>> {
+        GBModCategory::build(ROOT_CATEGORY).await
     }
 }
 
@@ -221,6 +227,276 @@ impl Itemized for Categories {
     }
 }
 
+/// Downloads running at once, the rest wait in the queue
+pub const MAX_DOWNLOADS: usize = 3;
+const BAR_WIDTH: usize = 20;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum JobState {
+    Queued,
+    Running,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+/// One file of a mod to download and register, identified by `id` since the same mod
+/// can be queued with different files
+#[derive(Clone)]
+pub struct Job {
+    pub id: usize,
+    pub entry: GBSearchEntry,
+    pub file_idx: usize,
+    pub file_name: String,
+    pub progress: Progress,
+    pub state: JobState,
+}
+
+impl Job {
+    /// Jobs with the same key write to the same place
+    fn key(&self) -> (usize, usize) {
+        (self.entry.row, self.file_idx)
+    }
+
+    fn is_active(&self) -> bool {
+        matches!(self.state, JobState::Queued | JobState::Running)
+    }
+
+    fn status(&self) -> String {
+        match &self.state {
+            JobState::Queued => "queued".to_string(),
+            JobState::Running => match self.progress.ratio() {
+                Some(1.0) => "extracting..".to_string(),
+                Some(ratio) => {
+                    let filled = (ratio * BAR_WIDTH as f64) as usize;
+                    let bar = "#".repeat(filled) + &"-".repeat(BAR_WIDTH - filled);
+                    format!("[{bar}] {:>3.0}%", ratio * 100.0)
+                }
+                None => format!("{} KiB", self.progress.done() / 1024),
+            },
+            JobState::Done => "done".to_string(),
+            JobState::Failed(e) => format!("failed: {e}"),
+            JobState::Cancelled => "cancelled".to_string(),
+        }
+    }
+}
+
+impl From<Job> for Row<'_> {
+    fn from(job: Job) -> Self {
+        let style = match job.state {
+            JobState::Done => Style::default().fg(Color::Green),
+            JobState::Failed(_) => Style::default().fg(Color::Red),
+            JobState::Cancelled => Style::default().fg(Color::DarkGray),
+            _ => Style::default(),
+        };
+        let name = format!("{} - {}", job.entry.name, job.file_name);
+        Row::new(vec![Text::from(vec![
+            Line::from(name),
+            Line::styled(job.status(), style),
+        ])])
+        .height(2)
+    }
+}
+
+#[derive(Default)]
+pub struct Downloads {
+    pub state: RefCell<TableState>,
+    pub content: Vec<Job>,
+    next_id: usize,
+}
+
+impl Downloads {
+    /// False when the same file of the same mod is already queued or running
+    pub fn push(&mut self, entry: GBSearchEntry, file_idx: usize, file_name: String) -> bool {
+        let key = (entry.row, file_idx);
+        if self
+            .content
+            .iter()
+            .any(|job| job.is_active() && job.key() == key)
+        {
+            return false;
+        }
+        self.next_id += 1;
+        self.content.push(Job {
+            id: self.next_id,
+            entry,
+            file_idx,
+            file_name,
+            progress: Progress::default(),
+            state: JobState::Queued,
+        });
+        let mut state = self.state.borrow_mut();
+        if state.selected().is_none() {
+            state.select(Some(0));
+        }
+        true
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut Job> {
+        self.content.iter_mut().find(|job| job.id == id)
+    }
+
+    pub fn running(&self) -> usize {
+        self.content
+            .iter()
+            .filter(|job| job.state == JobState::Running)
+            .count()
+    }
+
+    /// Queued or running, what quitting would throw away
+    pub fn active(&self) -> usize {
+        self.content.iter().filter(|job| job.is_active()).count()
+    }
+
+    /// Queued jobs that fit under `MAX_DOWNLOADS`, marked as running. One whose file
+    /// is already being downloaded by another job waits for that one to finish
+    pub fn start_queued(&mut self) -> Vec<Job> {
+        let mut free = MAX_DOWNLOADS.saturating_sub(self.running());
+        let mut busy: Vec<_> = self
+            .content
+            .iter()
+            .filter(|job| job.state == JobState::Running)
+            .map(Job::key)
+            .collect();
+        let mut started = Vec::new();
+        for job in &mut self.content {
+            if free == 0 {
+                break;
+            }
+            if job.state != JobState::Queued || busy.contains(&job.key()) {
+                continue;
+            }
+            job.state = JobState::Running;
+            busy.push(job.key());
+            free -= 1;
+            started.push(job.clone());
+        }
+        started
+    }
+
+    /// Drops finished jobs from the list, failed ones stay around to be retried
+    pub fn clear_done(&mut self) {
+        self.content.retain(|job| job.state != JobState::Done);
+        let selected = self.state.borrow().selected();
+        let last = self.content.len().checked_sub(1);
+        self.state
+            .borrow_mut()
+            .select(selected.zip(last).map(|(s, l)| s.min(l)));
+    }
+}
+
+impl Itemized for Downloads {
+    type T = Job;
+
+    fn content(&self) -> &Vec<Self::T> {
+        &self.content
+    }
+
+    fn content_mut(&mut self) -> &mut Vec<Self::T> {
+        &mut self.content
+    }
+
+    fn state(&self) -> &RefCell<TableState> {
+        &self.state
+    }
+}
+
+#[derive(Default)]
+pub enum Slide {
+    #[default]
+    Pending,
+    Loading,
+    Ready(RefCell<StatefulProtocol>),
+    Failed,
+}
+
+/// Full page of the highlighted browse entry, with the description already turned into
+/// lines since redrawing happens far more often than loading
+pub struct Detail {
+    pub page: GBModPage,
+    pub text: Vec<Line<'static>>,
+}
+
+impl Detail {
+    pub fn new(page: GBModPage) -> Detail {
+        let text = html_to_lines(&page.text);
+        Detail { page, text }
+    }
+}
+
+/// Preview of the highlighted mod in the manage view, `path` is None when it has none
+#[derive(Default)]
+pub struct LocalPreview {
+    pub path: Option<PathBuf>,
+    pub image: Slide,
+}
+
+/// Cells taken by each thumbnail in the gallery strip, borders included
+pub const THUMB_CELLS: (u16, u16) = (12, 5);
+
+/// Preview images of the popup's mod, fetched and decoded as they come up
+#[derive(Default)]
+pub struct Gallery {
+    pub media: Vec<GBPreviewMedia>,
+    pub slides: Vec<Slide>,
+    pub thumbs: Vec<Slide>,
+    pub page: usize,
+}
+
+impl Gallery {
+    pub fn new(media: Vec<GBPreviewMedia>) -> Self {
+        let pending = || media.iter().map(|_| Slide::Pending).collect();
+        Self {
+            slides: pending(),
+            thumbs: pending(),
+            media,
+            page: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.media.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.media.len()
+    }
+
+    pub fn next(&mut self) {
+        self.page = (self.page + 1).min(self.len().saturating_sub(1));
+    }
+
+    pub fn previous(&mut self) {
+        self.page = self.page.saturating_sub(1);
+    }
+
+    pub fn current(&self) -> Option<&Slide> {
+        self.slides.get(self.page)
+    }
+
+    /// The current slide and the one after it, if they haven't been asked for yet
+    pub fn wanted(&self) -> Vec<usize> {
+        (self.page..self.len().min(self.page + 2))
+            .filter(|i| matches!(self.slides[*i], Slide::Pending))
+            .collect()
+    }
+
+    pub fn wanted_thumbs(&self) -> Vec<usize> {
+        (0..self.len())
+            .filter(|i| matches!(self.thumbs[*i], Slide::Pending))
+            .collect()
+    }
+
+    /// Cancelled loads have to be asked for again
+    pub fn forget_loading(&mut self) {
+        self.slides
+            .iter_mut()
+            .chain(self.thumbs.iter_mut())
+            .filter(|s| matches!(s, Slide::Loading))
+            .for_each(|s| *s = Slide::Pending);
+    }
+}
+
 pub struct CyclicState<I, T> {
     cycle: Cycle<I>,
     len: usize,
@@ -259,3 +535,28 @@ where
         self.cycle();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn same_file_downloads_once() {
+        let entry: GBSearchEntry = serde_json::from_str(
+            r#"{"date_added": 0, "model_name": "Mod", "name": "Ky", "row": 7,
+            "game": {"row": 1, "name": "GGST"}, "category": {"name": "Skins"}}"#,
+        )
+        .unwrap();
+        let mut downloads = Downloads::default();
+        assert!(downloads.push(entry.clone(), 0, String::from("a.zip")));
+        assert!(!downloads.push(entry.clone(), 0, String::from("a.zip")));
+        assert!(downloads.push(entry.clone(), 1, String::from("b.zip")));
+        assert_eq!(downloads.start_queued().len(), 2);
+        // A retried copy of a running job has to wait for it
+        downloads.content[0].state = JobState::Cancelled;
+        assert!(downloads.push(entry, 0, String::from("a.zip")));
+        downloads.content[0].state = JobState::Running;
+        assert!(downloads.start_queued().is_empty());
+        assert_eq!(downloads.active(), 3);
+    }
+}
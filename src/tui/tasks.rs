@@ -0,0 +1,169 @@
+use std::{collections::HashMap, future::Future, panic::AssertUnwindSafe};
+
+use anyhow::Result;
+use futures::FutureExt;
+use log::{debug, trace};
+use ratatui_image::protocol::StatefulProtocol;
+use tokio::{sync::mpsc::UnboundedSender, task::AbortHandle};
+
+use super::{crash::panic_message, state::Detail};
+use crate::{
+    gamebanana::{
+        models::{category::GBModCategory, modpage::GBModPage, search_result::GBSearchEntry},
+        search::SearchPage,
+    },
+    modz::Mod,
+};
+
+/// Results of background work, applied to the app by `App::update`
+pub enum Message {
+    Categories(Result<Vec<GBModCategory>>),
+    SearchPage(Result<SearchPage>),
+    ModPage(Box<(GBSearchEntry, Result<GBModPage>)>),
+    Detail(Result<Box<Detail>>),
+    Registered(Result<Box<Mod>>),
+    /// A decoded preview, which one is told by the task kind
+    Image(Result<Box<StatefulProtocol>>),
+    /// The task panicked, already logged with a backtrace by the panic hook
+    Panicked(String),
+}
+
+/// At most one task of each kind runs, starting another one cancels the old one
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum TaskKind {
+    Connect,
+    Search,
+    ModPage,
+    Detail,
+    /// Gallery images by index, each loads on its own
+    Slide(usize),
+    Thumb(usize),
+    /// First preview of the highlighted local mod, read from the cache
+    LocalPreview,
+    /// Keyed by download job id so queued downloads don't cancel each other
+    Download(usize),
+}
+
+/// Which task a message came from, messages of replaced tasks are dropped
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TaskId {
+    pub kind: TaskKind,
+    seq: usize,
+}
+
+pub type Envelope = (TaskId, Message);
+
+pub struct Tasks {
+    tx: UnboundedSender<Envelope>,
+    seq: usize,
+    running: HashMap<TaskKind, (usize, AbortHandle)>,
+}
+
+impl Tasks {
+    pub fn new(tx: UnboundedSender<Envelope>) -> Self {
+        Self {
+            tx,
+            seq: 0,
+            running: HashMap::new(),
+        }
+    }
+
+    /// Runs `fut` in the background and posts what it returns
+    pub fn spawn<F>(&mut self, kind: TaskKind, fut: F)
+    where
+        F: Future<Output = Message> + Send + 'static,
+    {
+        self.cancel(kind);
+        self.seq += 1;
+        let id = TaskId {
+            kind,
+            seq: self.seq,
+        };
+        let tx = self.tx.clone();
+        debug!(task:? = kind; "Spawning");
+        let handle = tokio::spawn(async move {
+            let msg = AssertUnwindSafe(fut)
+                .catch_unwind()
+                .await
+                .unwrap_or_else(|p| Message::Panicked(panic_message(&*p).to_string()));
+            // Only fails once the app is gone, nobody to tell then
+            let _ = tx.send((id, msg));
+        });
+        self.running.insert(kind, (id.seq, handle.abort_handle()));
+    }
+
+    pub fn cancel(&mut self, kind: TaskKind) {
+        if let Some((_, handle)) = self.running.remove(&kind) {
+            trace!(task:? = kind; "Cancelling");
+            handle.abort();
+        }
+    }
+
+    /// Cancels every task whose kind matches, false if there were none
+    pub fn cancel_matching(&mut self, pred: impl Fn(TaskKind) -> bool) -> bool {
+        let kinds: Vec<TaskKind> = self.running.keys().copied().filter(|k| pred(*k)).collect();
+        kinds.iter().for_each(|k| self.cancel(*k));
+        !kinds.is_empty()
+    }
+
+    pub fn cancel_all(&mut self) {
+        self.running.drain().for_each(|(_, (_, h))| h.abort());
+    }
+
+    /// Marks the task as done, false if it was cancelled or replaced in the meantime
+    pub fn finish(&mut self, id: TaskId) -> bool {
+        match self.running.get(&id.kind) {
+            Some((seq, _)) if *seq == id.seq => {
+                self.running.remove(&id.kind);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_running(&self, kind: TaskKind) -> bool {
+        self.running.contains_key(&kind)
+    }
+
+    pub fn is_busy(&self) -> bool {
+        !self.running.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn replaced_tasks_are_stale() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut tasks = Tasks::new(tx);
+        tasks.spawn(TaskKind::Search, async {
+            tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+            Message::Categories(Ok(Vec::new()))
+        });
+        tasks.spawn(TaskKind::Search, async {
+            Message::Categories(Ok(Vec::new()))
+        });
+        let (id, _) = rx.recv().await.unwrap();
+        assert!(tasks.finish(id));
+        assert!(!tasks.is_busy());
+        tasks.spawn(TaskKind::Slide(0), async {
+            Message::Categories(Ok(Vec::new()))
+        });
+        let (id, _) = rx.recv().await.unwrap();
+        assert!(tasks.cancel_matching(|k| matches!(k, TaskKind::Slide(_))));
+        assert!(!tasks.finish(id));
+    }
+
+    #[tokio::test]
+    async fn panics_become_messages() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut tasks = Tasks::new(tx);
+        tasks.spawn(TaskKind::ModPage, async { panic!("bad page") });
+        let (id, msg) = rx.recv().await.unwrap();
+        assert!(tasks.finish(id));
+        assert!(matches!(msg, Message::Panicked(m) if m == "bad page"));
+    }
+}
@@ -6,6 +6,8 @@ use throbber::try_throbber;
 use util::*;
 mod browse;
 mod category;
+mod detail;
+mod downloads;
 mod gallery;
 mod help;
 mod manage;
@@ -13,12 +15,14 @@ mod popup;
 mod search;
 mod section;
 mod throbber;
+mod toasts;
 
 use crate::tui::ui::help::help_window;
 use std::rc::Rc;
 
 use browse::browse_view;
 use category::category;
+use downloads::downloads;
 use manage::manage_view;
 use popup::try_popup;
 use ratatui::{
@@ -27,6 +31,9 @@ use ratatui::{
 };
 use search::search_bar;
 use section::section;
+use toasts::{try_history, try_toasts};
+
+pub(super) use detail::html_to_lines;
 
 use super::app::{App, View};
 
@@ -45,18 +52,22 @@ pub fn show_ui(frame: &mut Frame, app: &mut App) {
             Constraint::Length(12),
             Constraint::Fill(2),
             Constraint::Fill(1),
+            Constraint::Fill(2),
         ])
         .split(view_and_side[1]);
     view_render(frame, app, view_chunks);
     side_render(frame, app, side_chunks);
     try_popup(frame, app, view_and_side[1]);
-    try_draw_gallery(frame, app, view_and_side[0])
+    try_draw_gallery(frame, app, view_and_side[0]);
+    try_toasts(frame, app, frame.area());
+    try_history(frame, app, frame.area());
 }
 
 fn side_render(frame: &mut Frame, app: &mut App, area: Rc<[Rect]>) {
     help_window(frame, app, area[0]);
     category(frame, app, area[1]);
     section(frame, app, area[2]);
+    downloads(frame, app, area[3]);
 }
 
 fn view_render(frame: &mut Frame, app: &mut App, area: Rc<[Rect]>) {
@@ -1,5 +1,5 @@
 use ratatui::{
-    layout::{Constraint, Rect},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
     widgets::{Block, Borders, Row, Table},
     Frame,
@@ -7,25 +7,35 @@ use ratatui::{
 
 use crate::tui::app::{App, Window};
 
-use super::hide_unfocused;
+use super::{detail::detail_pane, hide_unfocused};
 
 pub fn browse_view(frame: &mut Frame, app: &mut App, area: Rect) {
+    let halves = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(area);
     let widths = [
         Constraint::Length(35),
         Constraint::Length(20),
+        Constraint::Length(15),
         Constraint::Max(6),
         Constraint::Max(6),
         Constraint::Max(10),
         Constraint::Fill(1),
     ];
+    let title = match app.page_label() {
+        Some(label) => format!("[2]-Browse ({label}){}", app.connectivity_label()),
+        None => format!("[2]-Browse{}", app.connectivity_label()),
+    };
     let block = hide_unfocused(
-        Block::default().borders(Borders::ALL).title("[2]-Browse"),
+        Block::default().borders(Borders::ALL).title(title),
         app,
         Window::Main,
     );
     let header = Row::new(vec![
         "Name",
         "Character",
+        "Submitter",
         "Views",
         "Likes",
         "Downloads",
@@ -38,5 +48,6 @@ pub fn browse_view(frame: &mut Frame, app: &mut App, area: Rect) {
         .widths(widths)
         .block(block)
         .row_highlight_style(Style::default().bg(Color::DarkGray));
-    frame.render_stateful_widget(text, area, &mut app.search_state().borrow_mut());
+    frame.render_stateful_widget(text, halves[0], &mut app.search_state().borrow_mut());
+    detail_pane(frame, app, halves[1]);
 }
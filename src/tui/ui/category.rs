@@ -11,7 +11,9 @@ use super::hide_unfocused;
 
 pub fn category(frame: &mut Frame, app: &mut App, area: Rect) {
     let block = hide_unfocused(
-        Block::default().title("[3]-Category").borders(Borders::ALL),
+        Block::default()
+            .title(format!("[3]-Category{}", app.connectivity_label()))
+            .borders(Borders::ALL),
         app,
         Window::Category,
     );
@@ -0,0 +1,184 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+use regex::Regex;
+use std::sync::LazyLock;
+
+use crate::{
+    gamebanana::{decode_entities, format_date, human_size, models::search_result::GBSearchEntry},
+    tui::{
+        app::{App, Window},
+        state::Itemized,
+    },
+};
+
+use super::hide_unfocused;
+
+/// Everything known about the highlighted browse entry, the full page fills in the
+/// description, tags and files once it's loaded
+pub fn detail_pane(frame: &mut Frame, app: &App, area: Rect) {
+    let block = hide_unfocused(
+        Block::default().borders(Borders::ALL).title("Details"),
+        app,
+        Window::Main,
+    );
+    let Some(entry) = app.online_items.select() else {
+        frame.render_widget(block, area);
+        return;
+    };
+    let detail = app.detail.as_ref().filter(|d| d.page.row == entry.row);
+    let mut lines = summary(app, entry);
+    match detail {
+        Some(detail) => {
+            let page = &detail.page;
+            if !page.tags.is_empty() {
+                let tags: Vec<String> = page.tags.iter().map(ToString::to_string).collect();
+                lines.push(Line::from(format!("Tags: {}", tags.join(", "))));
+            }
+            lines.push(Line::default());
+            lines.push(Line::from("Files").bold());
+            for file in &page.files {
+                lines.push(Line::from(format!(
+                    "  {} - {}, {} downloads",
+                    file.file,
+                    human_size(file.filesize as u64),
+                    file.download_count
+                )));
+            }
+            lines.push(Line::default());
+            if !page.description.is_empty() {
+                lines.push(Line::from(page.description.clone()).italic());
+            }
+            lines.extend(detail.text.iter().cloned());
+        }
+        None => {
+            lines.push(Line::default());
+            lines.push(Line::from("Loading details..").fg(Color::DarkGray));
+            lines.push(Line::from(entry.description.clone()));
+        }
+    }
+    let text = Paragraph::new(Text::from(lines))
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .scroll((app.detail_scroll, 0));
+    frame.render_widget(text, area);
+}
+
+fn summary(app: &App, entry: &GBSearchEntry) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(entry.name.clone()).bold()];
+    let mut by = Vec::new();
+    if let Some(submitter) = &entry.submitter {
+        by.push(Span::from(format!("by {} - ", submitter.name)));
+    }
+    by.push(Span::from(entry.category.name.clone()));
+    lines.push(Line::from(by));
+    if entry.date_added != 0 {
+        lines.push(Line::from(format!(
+            "Added {}, updated {}",
+            format_date(entry.date_added),
+            format_date(entry.date_updated.max(entry.date_added))
+        )));
+    }
+    lines.push(Line::from(format!(
+        "{} views, {} likes, {} downloads",
+        entry.view_count, entry.like_count, entry.download_count
+    )));
+    let local = match app.local_status(entry.row) {
+        None => Span::from("not downloaded"),
+        Some(false) => Span::styled("downloaded", Style::new().fg(Color::Yellow)),
+        Some(true) => Span::styled("installed", Style::new().fg(Color::Green)),
+    };
+    lines.push(Line::from(vec![Span::from("Local: "), local]));
+    lines
+}
+
+fn tag_style(tag: &str) -> Option<Style> {
+    match tag {
+        "b" | "strong" => Some(Style::new().bold()),
+        "i" | "em" => Some(Style::new().italic()),
+        "u" => Some(Style::new().underlined()),
+        "a" => Some(Style::new().fg(Color::Blue).underlined()),
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => Some(Style::new().bold().fg(Color::Cyan)),
+        _ => None,
+    }
+}
+
+fn breaks_line(tag: &str, closing: bool) -> bool {
+    match tag {
+        "br" | "ul" | "ol" => true,
+        "p" | "div" | "li" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => closing,
+        _ => false,
+    }
+}
+
+/// Tags, text between them, or a stray `<`
+static TOKENS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)<(/?)([a-z0-9]+)[^>]*>|[^<]+|<").unwrap());
+
+/// Like `html_to_text` but keeps bold, italics, links and headings as styles
+pub fn html_to_lines(html: &str) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::default()];
+    let mut styles = vec![Style::default()];
+    for cap in TOKENS.captures_iter(html) {
+        let line = lines.last_mut().expect("Never empty");
+        let Some(tag) = cap.get(2) else {
+            let text = decode_entities(&cap[0].replace(['\n', '\r'], " "));
+            let style = *styles.last().expect("Never empty");
+            line.push_span(Span::styled(text, style));
+            continue;
+        };
+        let tag = tag.as_str().to_lowercase();
+        let closing = !cap[1].is_empty();
+        if tag == "li" && !closing {
+            line.push_span("• ");
+        }
+        match tag_style(&tag) {
+            Some(_) if closing && styles.len() > 1 => drop(styles.pop()),
+            Some(style) if !closing => {
+                styles.push(styles.last().expect("Never empty").patch(style))
+            }
+            _ => (),
+        }
+        if breaks_line(&tag, closing) {
+            lines.push(Line::default());
+        }
+    }
+    // Trim lines and squash runs of blank ones like html_to_text does
+    let is_blank = |l: &Line| l.spans.iter().all(|s| s.content.trim().is_empty());
+    let mut out: Vec<Line<'static>> = Vec::new();
+    for line in lines {
+        if is_blank(&line) && out.last().is_none_or(is_blank) {
+            continue;
+        }
+        out.push(if is_blank(&line) {
+            Line::default()
+        } else {
+            line
+        });
+    }
+    while out.last().is_some_and(is_blank) {
+        out.pop();
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn html_keeps_styles() {
+        let lines = html_to_lines(
+            "<h2>Ky</h2><p>Some <b>bold &amp; <i>both</i></b></p><br><br><ul><li>one</li></ul>",
+        );
+        let text: Vec<String> = lines.iter().map(ToString::to_string).collect();
+        assert_eq!(text, vec!["Ky", "Some bold & both", "", "• one"]);
+        let both = &lines[1].spans[2];
+        assert_eq!(both.content, "both");
+        assert_eq!(both.style, Style::new().bold().italic());
+    }
+}
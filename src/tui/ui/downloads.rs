@@ -0,0 +1,29 @@
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders, Table},
+    Frame,
+};
+
+use crate::tui::{
+    app::{App, Window},
+    state::MAX_DOWNLOADS,
+};
+
+use super::hide_unfocused;
+
+pub fn downloads(frame: &mut Frame, app: &App, area: Rect) {
+    let running = app.downloads.running();
+    let block = hide_unfocused(
+        Block::default()
+            .title(format!("[5]-Downloads {running}/{MAX_DOWNLOADS}"))
+            .borders(Borders::ALL),
+        app,
+        Window::Downloads,
+    );
+    let table = Table::new(app.downloads.content.clone(), [Constraint::Fill(1)])
+        .widths([Constraint::Fill(1)])
+        .block(block)
+        .row_highlight_style(Style::default().fg(Color::Yellow));
+    frame.render_stateful_widget(table, area, &mut app.downloads.state.borrow_mut());
+}
@@ -1,22 +1,90 @@
-use log::info;
-use ratatui::{layout::Rect, Frame};
-use ratatui_image::StatefulImage;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use ratatui_image::{FilterType, Resize, StatefulImage};
 
-use crate::tui::app::App;
+use crate::tui::{
+    app::App,
+    state::{Slide, THUMB_CELLS},
+};
 
 use super::centered_rect;
 
-// XXX: Kinda stupid that this is the only function here but everything is designed
-// around app state oh well
-pub fn try_draw_gallery(frame: &mut Frame<'_>, app: &mut App, area: Rect) {
-    let rect = centered_rect(50, 50, area);
-    if let Some((_, state)) = app.image_states.get_index(app.gallery_page()) {
-        info!(
-            "gallery_page: {}, images: {}",
-            app.gallery_page(),
-            app.image_states.len()
-        );
-        frame.render_stateful_widget(StatefulImage::default(), rect, &mut state.borrow_mut());
-        frame.set_cursor_position((frame.area().right(), frame.area().bottom()));
+const THUMB_WIDTH: u16 = THUMB_CELLS.0;
+const THUMB_HEIGHT: u16 = THUMB_CELLS.1;
+
+pub fn try_draw_gallery(frame: &mut Frame<'_>, app: &App, area: Rect) {
+    let gallery = &app.gallery;
+    if gallery.is_empty() {
+        return;
+    }
+    let rect = centered_rect(50, 60, area);
+    let [image, strip, indicator] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(THUMB_HEIGHT),
+            Constraint::Length(1),
+        ])
+        .areas(rect);
+    frame.render_widget(Clear, rect);
+    draw_slide(frame, gallery.current(), image);
+    draw_strip(frame, app, strip);
+    let status = match gallery.current() {
+        Some(Slide::Failed) => " - failed to load",
+        Some(Slide::Ready(_)) => "",
+        _ => " - loading..",
+    };
+    let label = format!(
+        "{}/{}{status}  h/l to scroll",
+        gallery.page + 1,
+        gallery.len()
+    );
+    frame.render_widget(Paragraph::new(label).centered(), indicator);
+}
+
+pub fn draw_slide(frame: &mut Frame<'_>, slide: Option<&Slide>, area: Rect) {
+    match slide {
+        Some(Slide::Ready(image)) => {
+            // Scale rather than fit so small previews grow to the area, aspect kept
+            let widget = StatefulImage::default().resize(Resize::Scale(Some(FilterType::Triangle)));
+            frame.render_stateful_widget(widget, area, &mut image.borrow_mut())
+        }
+        Some(Slide::Failed) => frame.render_widget(Paragraph::new("x").centered(), area),
+        _ => frame.render_widget(Paragraph::new("..").centered(), area),
+    }
+}
+
+/// As many thumbnails as fit, scrolled so the current one stays in view
+fn draw_strip(frame: &mut Frame<'_>, app: &App, area: Rect) {
+    let gallery = &app.gallery;
+    let fits = (area.width / THUMB_WIDTH).max(1) as usize;
+    let first = gallery
+        .page
+        .saturating_sub(fits / 2)
+        .min(gallery.len().saturating_sub(fits));
+    let shown = fits.min(gallery.len() - first);
+    let cells = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Length(THUMB_WIDTH); shown])
+        .flex(ratatui::layout::Flex::Center)
+        .split(area);
+    for (i, cell) in (first..first + shown).zip(cells.iter()) {
+        let color = if i == gallery.page {
+            Color::Yellow
+        } else {
+            Color::DarkGray
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(color))
+            .title(Line::from((i + 1).to_string()));
+        let inner = block.inner(*cell);
+        frame.render_widget(block, *cell);
+        draw_slide(frame, gallery.thumbs.get(i), inner);
     }
 }
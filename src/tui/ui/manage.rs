@@ -1,23 +1,36 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
-    widgets::{Block, Borders, Row, Table},
+    widgets::{Block, Borders, Paragraph, Row, Table},
     Frame,
 };
 
-use crate::tui::app::{App, View, ViewDir, Window};
+use crate::tui::{
+    app::{App, View, ViewDir, Window},
+    state::{Itemized, Slide},
+};
+
+use super::gallery::draw_slide;
+
+/// Rows for the preview of the highlighted mod under the tables
+const PREVIEW_HEIGHT: u16 = 14;
 
 pub fn manage_view(frame: &mut Frame, app: &App, area: Rect) {
-    let header = Row::new(vec!["Name", "Character", "Variant"]);
+    let header = Row::new(vec!["Name", "Character", "Submitter", "Variant"]);
     let widths = [
         Constraint::Length(35),
         Constraint::Length(20),
+        Constraint::Length(15),
         Constraint::Fill(1),
     ];
+    let [tables, preview] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Fill(1), Constraint::Length(PREVIEW_HEIGHT)])
+        .areas(area);
     let halves = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(area);
+        .split(tables);
     frame.render_stateful_widget(
         left_table(app, widths).header(header.clone()),
         halves[0],
@@ -28,9 +41,34 @@ pub fn manage_view(frame: &mut Frame, app: &App, area: Rect) {
         halves[1],
         &mut app.unstaged_items.state.borrow_mut(),
     );
+    preview_pane(frame, app, preview);
+}
+
+fn preview_pane(frame: &mut Frame, app: &App, area: Rect) {
+    let selected = match app.view {
+        View::Manage(dir) => app.local_items(dir).select(),
+        View::Browse => None,
+    };
+    let title = match selected {
+        Some(m) => format!("Preview - {}", m.name),
+        None => "Preview".to_string(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .style(Style::default().fg(Color::DarkGray));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let note = match (&app.local_preview.path, &app.local_preview.image) {
+        (None, _) if selected.is_some() => "No preview saved with this mod",
+        (None, _) => "",
+        (Some(_), Slide::Failed) => "Preview isn't cached anymore",
+        (Some(_), image) => return draw_slide(frame, Some(image), inner),
+    };
+    frame.render_widget(Paragraph::new(note).centered(), inner);
 }
 
-fn left_table(app: &App, widths: [Constraint; 3]) -> Table<'_> {
+fn left_table(app: &App, widths: [Constraint; 4]) -> Table<'_> {
     let block = Block::default()
         .borders(Borders::ALL)
         .title("[2]-Staged")
@@ -44,7 +82,7 @@ fn left_table(app: &App, widths: [Constraint; 3]) -> Table<'_> {
         .row_highlight_style(Color::Green)
 }
 
-fn right_table(app: &App, widths: [Constraint; 3]) -> Table<'_> {
+fn right_table(app: &App, widths: [Constraint; 4]) -> Table<'_> {
     let block = Block::default()
         .borders(Borders::ALL)
         .title("Unstaged")
@@ -29,6 +29,5 @@ pub fn try_throbber(frame: &mut Frame, app: &mut App, area: Rect) {
             .throbber_set(throbber_widgets_tui::ASCII)
             .use_type(throbber_widgets_tui::WhichUse::Spin);
         frame.render_stateful_widget(full, rect, state);
-        app.throb();
     }
 }
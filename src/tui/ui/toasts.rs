@@ -0,0 +1,87 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::tui::{app::App, notify::Severity};
+
+use super::centered_rect;
+
+fn color(severity: Severity) -> Color {
+    match severity {
+        Severity::Info => Color::LightGreen,
+        Severity::Warning => Color::Yellow,
+        Severity::Error => Color::LightRed,
+    }
+}
+
+/// Stacks fresh notifications in the bottom right corner, newest at the bottom
+pub fn try_toasts(frame: &mut Frame, app: &App, area: Rect) {
+    let toasts: Vec<_> = app.notifications.toasts().collect();
+    if toasts.is_empty() || app.notifications.show_history {
+        return;
+    }
+    let width = (area.width / 3).max(30).min(area.width);
+    let column = Rect {
+        x: area.right() - width,
+        width,
+        ..area
+    };
+    let mut constraints = vec![Constraint::Fill(1)];
+    constraints.extend(toasts.iter().map(|_| Constraint::Length(4)));
+    let slots = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(column);
+    for (toast, slot) in toasts.into_iter().zip(slots.iter().skip(1)) {
+        let style = Style::default().fg(color(toast.severity));
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(style)
+            .title(toast.severity.to_string());
+        let text = Paragraph::new(toast.label())
+            .wrap(Wrap { trim: true })
+            .block(block);
+        frame.render_widget(Clear, *slot);
+        frame.render_widget(text, *slot);
+    }
+}
+
+pub fn try_history(frame: &mut Frame, app: &App, area: Rect) {
+    let notes = &app.notifications;
+    if !notes.show_history {
+        return;
+    }
+    let items: Vec<ListItem> = notes
+        .history()
+        .map(|n| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:<6}", n.severity), color(n.severity)),
+                Span::from(format!("{:>5} ", ago(n.at.elapsed().as_secs()))),
+                Span::from(n.label()),
+            ]))
+        })
+        .collect();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Messages ({}) - j/k scroll, m close", notes.len()));
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().fg(Color::White).bg(Color::DarkGray));
+    let mut state = ListState::default();
+    state.select(notes.len().checked_sub(notes.scroll + 1));
+    let rect = centered_rect(70, 60, area);
+    frame.render_widget(Clear, rect);
+    frame.render_stateful_widget(list, rect, &mut state);
+}
+
+fn ago(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m", secs / 60),
+        _ => format!("{}h", secs / 3600),
+    }
+}